@@ -0,0 +1,50 @@
+use std::fmt;
+use std::net::IpAddr;
+
+/// An address and prefix length, see [`crate::Interface::to_cidr()`].
+/// Formats as "192.168.0.42/24". Like [`crate::Interface::cidr()`], the
+/// address is the interface's address, not the network address.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Cidr {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    pub const fn new(address: IpAddr, prefix_len: u8) -> Self {
+        Cidr {
+            address,
+            prefix_len,
+        }
+    }
+
+    pub const fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub const fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// The network address, e.g., 192.168.0.0 for 192.168.0.42/24.
+    pub fn network(&self) -> IpAddr {
+        match self.address {
+            IpAddr::V4(addr) => {
+                let bits = u32::from(self.prefix_len.min(32));
+                let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+                IpAddr::V4((u32::from(addr) & mask).into())
+            }
+            IpAddr::V6(addr) => {
+                let bits = u32::from(self.prefix_len.min(128));
+                let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+                IpAddr::V6((u128::from(addr) & mask).into())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
@@ -0,0 +1,133 @@
+use crate::scope_rank;
+use crate::up;
+use crate::Family;
+use crate::Interface;
+use crate::Scope;
+use std::io;
+use std::net::IpAddr;
+
+/// A network device and its addresses. Where [`up()`] produces one
+/// [`Interface`] per address, [`devices()`] produces one `Device` per
+/// network interface.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Device {
+    name: String,
+    index: u32,
+    interfaces: Vec<Interface>,
+}
+
+impl Device {
+    /// Device name, e.g., "eth0". The name of the first address when the
+    /// addresses have different names, e.g., "eth0" and alias "eth0:1".
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Interface index, the number that e.g. if_nametoindex(3) returns.
+    /// Zero when unknown.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The device's addresses, one [`Interface`] per address.
+    pub fn interfaces(&self) -> &[Interface] {
+        &self.interfaces
+    }
+
+    /// The device's addresses.
+    pub fn addresses(&self) -> impl Iterator<Item = &IpAddr> {
+        self.interfaces.iter().map(|ifa| ifa.address())
+    }
+
+    /// The device's addresses of address family |family| and scope |scope|,
+    /// e.g., only global IPv6 addresses.
+    pub fn addresses_matching(
+        &self,
+        family: Family,
+        scope: Scope,
+    ) -> Vec<IpAddr> {
+        self.addresses()
+            .filter(|addr| Family::of(addr) == family)
+            .filter(|addr| Scope::of(addr) == scope)
+            .copied()
+            .collect()
+    }
+
+    /// The device's addresses in CIDR notation, comma-separated, e.g.,
+    /// "192.168.1.5/24, fe80::1%eth0/64". IPv6 link-local addresses
+    /// have a zone suffix.
+    pub fn addresses_string(&self) -> String {
+        let cidrs = self.interfaces.iter().map(|ifa| {
+            let (address, prefix_len) = ifa.cidr();
+
+            if ifa.zone_id().is_some() {
+                format!("{}%{}/{}", address, ifa.name(), prefix_len)
+            } else {
+                format!("{}/{}", address, prefix_len)
+            }
+        });
+
+        cidrs.collect::<Vec<_>>().join(", ")
+    }
+
+    /// The device's canonical address of address family |family|: the
+    /// first address that isn't secondary, deprecated or tentative, with
+    /// global addresses preferred over unique local and private addresses,
+    /// and those over link-local addresses. None when the device has no
+    /// address of that family.
+    pub fn primary_address(&self, family: Family) -> Option<IpAddr> {
+        self.interfaces
+            .iter()
+            .filter(|ifa| Family::of(ifa.address()) == family)
+            .min_by_key(|ifa| {
+                (
+                    ifa.is_secondary(),
+                    ifa.is_deprecated(),
+                    ifa.is_tentative(),
+                    scope_rank(ifa.address()),
+                )
+            })
+            .map(|ifa| *ifa.address())
+    }
+
+    /// The device's IPv6 addresses, best choice for new connections first:
+    /// preferred before deprecated addresses, then global before unique
+    /// local before link-local addresses.
+    pub fn ipv6_sorted(&self) -> Vec<IpAddr> {
+        let mut ifas = self
+            .interfaces
+            .iter()
+            .filter(|ifa| ifa.address().is_ipv6())
+            .collect::<Vec<_>>();
+
+        ifas.sort_by_key(|ifa| {
+            (ifa.is_deprecated(), scope_rank(ifa.address()))
+        });
+        ifas.into_iter().map(|ifa| *ifa.address()).collect()
+    }
+}
+
+/// Returns the network devices that have at least one address, in the
+/// order in which [`up()`] first reports them. Addresses are grouped by
+/// interface index, which is stable for the lifetime of the interface,
+/// or by name when the index is unknown.
+pub fn devices() -> io::Result<Vec<Device>> {
+    let mut devices: Vec<Device> = Vec::new();
+
+    for ifa in up()? {
+        let dev = devices
+            .iter_mut()
+            .find(|dev| dev.interfaces[0].is_sibling_of(&ifa));
+
+        match dev {
+            Some(dev) => dev.interfaces.push(ifa),
+            None => devices.push(Device {
+                name: ifa.name().to_owned(),
+                index: ifa.index(),
+                interfaces: vec![ifa],
+            }),
+        }
+    }
+
+    Ok(devices)
+}
@@ -0,0 +1,330 @@
+// Line-based text format for interfaces. One interface per line, fields
+// are tab-separated key=value pairs. Missing fields take their default
+// value and unknown fields are ignored so that dumps made by older or newer
+// versions of this crate can still be loaded.
+use crate::up;
+use crate::Interface;
+use crate::OperStatus;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Returns the interfaces that [`up()`] reports in a stable textual format
+/// that can be turned back into interfaces with [`load()`]. Useful for
+/// recording the interfaces of a machine and replaying them in tests.
+pub fn dump() -> io::Result<String> {
+    let mut s = String::new();
+
+    for ifa in up()? {
+        write(&mut s, &ifa);
+    }
+
+    Ok(s)
+}
+
+/// Parses the output of [`dump()`].
+pub fn load(s: &str) -> io::Result<Vec<Interface>> {
+    load_lines(s, 1)
+}
+
+/// Writes the interfaces that [`up()`] reports to |w|, in a format that
+/// starts with a version header, for reading back with
+/// [`restore_from_reader()`], possibly on a different machine.
+pub fn capture_to_writer(mut w: impl Write) -> io::Result<()> {
+    let s = dump()?;
+    write!(w, "{HEADER} {VERSION}\n{s}")?;
+    w.flush()
+}
+
+/// Reads interfaces that [`capture_to_writer()`] wrote. Fails with
+/// io::ErrorKind::InvalidData when the data isn't a capture or when it
+/// was written by a newer, incompatible version of this crate.
+pub fn restore_from_reader(mut r: impl Read) -> io::Result<Vec<Interface>> {
+    let mut s = String::new();
+    r.read_to_string(&mut s)?;
+
+    let (header, rest) = s.split_once('\n').unwrap_or((&s, ""));
+
+    let version = header
+        .strip_prefix(HEADER)
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .ok_or_else(|| {
+            let msg = "not a netif capture";
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        })?;
+
+    if version > VERSION {
+        let msg = format!("unsupported capture version {version}");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    }
+
+    load_lines(rest, 2)
+}
+
+/// First line of a capture, followed by the version number. Bump the
+/// version when changing the format in a way that older versions of this
+/// crate can't read; adding fields doesn't count, those are ignored.
+const HEADER: &str = "netif-capture";
+const VERSION: u32 = 1;
+
+fn load_lines(s: &str, first: usize) -> io::Result<Vec<Interface>> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            read(line).map_err(|msg| {
+                let msg = format!("line {}: {}", i + first, msg);
+                io::Error::new(io::ErrorKind::InvalidData, msg)
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn write(s: &mut String, ifa: &Interface) {
+    let opt = |v: Option<u32>| v.map_or("-".to_owned(), |v| v.to_string());
+    let flag = |v: bool| if v { "1" } else { "0" };
+    let [b0, b1, b2, b3, b4, b5] = ifa.mac;
+
+    let lease_expiry = ifa.lease_expiry.map_or("-".to_owned(), |t| {
+        let t = t.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        format!("{}.{:09}", t.as_secs(), t.subsec_nanos())
+    });
+
+    let _ = writeln!(
+        s,
+        "name={}\tindex={}\tflags={}\t\
+         mac={b0:02x}:{b1:02x}:{b2:02x}:{b3:02x}:{b4:02x}:{b5:02x}\t\
+         hw_type={}\taddress={}\tnetmask={}\tscope_id={}\t\
+         secondary={}\tmetric={}\tmulticast={}\tlease_expiry={}\t\
+         tentative={}\tdeprecated={}\tdestination={}\t\
+         broadcast={}\tspeed={}\tdormant={}\t\
+         anycast={}\tmtu={}\t\
+         kernel_scope={}\tlink_addr={}\toper_status={}",
+        escape(&ifa.name),
+        ifa.index,
+        ifa.flags,
+        ifa.hw_type,
+        ifa.address,
+        ifa.netmask,
+        opt(ifa.scope_id),
+        flag(ifa.secondary),
+        opt(ifa.metric),
+        flag(ifa.multicast),
+        lease_expiry,
+        flag(ifa.tentative),
+        flag(ifa.deprecated),
+        ifa.destination.map_or("-".to_owned(), |v| v.to_string()),
+        ifa.broadcast.map_or("-".to_owned(), |v| v.to_string()),
+        ifa.speed.map_or("-".to_owned(), |v| v.to_string()),
+        flag(ifa.dormant),
+        flag(ifa.anycast),
+        opt(ifa.mtu),
+        opt(ifa.kernel_scope.map(|scope| u8::from(scope).into())),
+        hex(&ifa.link_addr),
+        ifa.oper_status.to_rfc2863(),
+    );
+}
+
+fn read(line: &str) -> Result<Interface, String> {
+    let field = |key: &str| {
+        line.split('\t')
+            .filter_map(|kv| kv.split_once('='))
+            .find(|&(k, _)| k == key)
+            .map(|(_, v)| v)
+    };
+
+    let name = field("name").ok_or("missing name")?;
+    let name = unescape(name)?;
+    let address: IpAddr = parse(field("address").ok_or("missing address")?)?;
+    let netmask: IpAddr = parse(field("netmask").ok_or("missing netmask")?)?;
+
+    let mut ifa = Interface::new(name, address, netmask);
+
+    if let Some(v) = field("index") {
+        ifa.index = parse(v)?;
+    }
+
+    if let Some(v) = field("flags") {
+        ifa.flags = parse(v)?;
+    }
+
+    if let Some(v) = field("mac") {
+        ifa.mac = parse_mac(v)?;
+    }
+
+    if let Some(v) = field("hw_type") {
+        ifa.hw_type = parse(v)?;
+    }
+
+    if let Some(v) = field("scope_id") {
+        ifa.scope_id = parse_opt(v)?;
+    }
+
+    if let Some(v) = field("secondary") {
+        ifa.secondary = parse_flag(v)?;
+    }
+
+    if let Some(v) = field("metric") {
+        ifa.metric = parse_opt(v)?;
+    }
+
+    if let Some(v) = field("multicast") {
+        ifa.multicast = parse_flag(v)?;
+    }
+
+    if let Some(v) = field("lease_expiry") {
+        ifa.lease_expiry = parse_time(v)?;
+    }
+
+    if let Some(v) = field("tentative") {
+        ifa.tentative = parse_flag(v)?;
+    }
+
+    if let Some(v) = field("deprecated") {
+        ifa.deprecated = parse_flag(v)?;
+    }
+
+    if let Some(v) = field("destination") {
+        ifa.destination = parse_opt(v)?;
+    }
+
+    if let Some(v) = field("broadcast") {
+        ifa.broadcast = parse_opt(v)?;
+    }
+
+    if let Some(v) = field("speed") {
+        ifa.speed = parse_opt(v)?;
+    }
+
+    if let Some(v) = field("dormant") {
+        ifa.dormant = parse_flag(v)?;
+    }
+
+    if let Some(v) = field("anycast") {
+        ifa.anycast = parse_flag(v)?;
+    }
+
+    if let Some(v) = field("mtu") {
+        ifa.mtu = parse_opt(v)?;
+    }
+
+    if let Some(v) = field("kernel_scope") {
+        ifa.kernel_scope = parse_opt::<u8>(v)?.map(From::from);
+    }
+
+    // Dumps from before link_addr existed only have the MAC address.
+    match field("link_addr") {
+        Some(v) => ifa.link_addr = parse_hex(v)?,
+        None if ifa.mac != [0; 6] => ifa.link_addr = ifa.mac.to_vec(),
+        None => {}
+    }
+
+    if let Some(v) = field("oper_status") {
+        ifa.oper_status = OperStatus::from_rfc2863(parse(v)?);
+    }
+
+    Ok(ifa)
+}
+
+fn parse<T: FromStr>(v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("bad value: {v}"))
+}
+
+fn parse_opt<T: FromStr>(v: &str) -> Result<Option<T>, String> {
+    if v == "-" {
+        Ok(None)
+    } else {
+        parse(v).map(Some)
+    }
+}
+
+fn parse_flag(v: &str) -> Result<bool, String> {
+    match v {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(format!("bad flag: {v}")),
+    }
+}
+
+fn parse_mac(v: &str) -> Result<[u8; 6], String> {
+    let mac = parse_hex(v).map_err(|_| format!("bad mac: {v}"))?;
+    mac.try_into().map_err(|_| format!("bad mac: {v}"))
+}
+
+/// Colon-separated hex octets, "-" if there are none.
+fn hex(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "-".to_owned();
+    }
+
+    let octets: Vec<_> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    octets.join(":")
+}
+
+fn parse_hex(v: &str) -> Result<Vec<u8>, String> {
+    if v == "-" {
+        return Ok(Vec::new());
+    }
+
+    v.split(':')
+        .map(|octet| match octet.len() {
+            1 | 2 => u8::from_str_radix(octet, 16).ok(),
+            _ => None,
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("bad hex: {v}"))
+}
+
+fn parse_time(v: &str) -> Result<Option<SystemTime>, String> {
+    if v == "-" {
+        return Ok(None);
+    }
+
+    let (secs, nanos) = v.split_once('.').unwrap_or((v, "0"));
+    let t = Duration::new(parse(secs)?, parse(nanos)?);
+
+    Ok(Some(SystemTime::UNIX_EPOCH + t))
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(s: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => return Err(format!("bad escape: {s}")),
+        }
+    }
+
+    Ok(unescaped)
+}
@@ -0,0 +1,127 @@
+use crate::up_with;
+use crate::Family;
+use crate::Interface;
+use std::io;
+use std::net::IpAddr;
+
+/// Returns a builder for listing interfaces that match some criteria,
+/// e.g., `enumerate().ipv4_only().skip_loopback().run()`. Without
+/// criteria, [`Enumerator::run()`] returns what [`up()`](crate::up) returns.
+pub fn enumerate() -> Enumerator {
+    Enumerator::default()
+}
+
+/// See [`enumerate()`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Enumerator {
+    family: Option<Family>,
+    skip_loopback: bool,
+    include_down: bool,
+    pattern: Option<String>,
+    sorted: bool,
+}
+
+impl Enumerator {
+    /// Only IPv4 addresses.
+    pub fn ipv4_only(mut self) -> Self {
+        self.family = Some(Family::V4);
+        self
+    }
+
+    /// Only IPv6 addresses.
+    pub fn ipv6_only(mut self) -> Self {
+        self.family = Some(Family::V6);
+        self
+    }
+
+    /// Leave out loopback addresses.
+    pub fn skip_loopback(mut self) -> Self {
+        self.skip_loopback = true;
+        self
+    }
+
+    /// Include interfaces that are down, like [`crate::all()`] does.
+    pub fn include_down(mut self) -> Self {
+        self.include_down = true;
+        self
+    }
+
+    /// Only interfaces whose name matches |pattern|, where `*` matches any
+    /// number of characters and `?` matches exactly one, e.g., "eth*".
+    pub fn name_matches(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Sort the results so that they don't depend on the order in which
+    /// the operating system reports interfaces: by metric, lowest first
+    /// and unknown last, then by name, then IPv4 before IPv6, then by
+    /// address. Makes the output of two runs diffable.
+    pub fn sorted(mut self) -> Self {
+        self.sorted = true;
+        self
+    }
+
+    /// Lists the interfaces that match.
+    pub fn run(&self) -> io::Result<Vec<Interface>> {
+        // Lets the operating system filter by family where possible.
+        let mut ifas: Vec<_> = up_with(self.include_down, self.family)?
+            .filter(|ifa| !self.skip_loopback || !ifa.address().is_loopback())
+            .filter(|ifa| match &self.pattern {
+                Some(pattern) => glob(pattern, ifa.name()),
+                None => true,
+            })
+            .collect();
+
+        if self.sorted {
+            sort(&mut ifas);
+        }
+
+        Ok(ifas)
+    }
+}
+
+/// See [`Enumerator::sorted()`].
+pub(crate) fn sort(ifas: &mut [Interface]) {
+    fn key(ifa: &Interface) -> (bool, Option<u32>, &str, Family, &IpAddr) {
+        let metric = ifa.metric();
+        let family = Family::of(ifa.address());
+        (metric.is_none(), metric, ifa.name(), family, ifa.address())
+    }
+
+    ifas.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
+/// True if |name| matches |pattern|, a shell-style wildcard pattern that
+/// only knows `*` and `?`.
+pub(crate) fn glob(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None; // Position of the last '*' and what it ate.
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, eaten)) => {
+                    // Let the '*' eat one more character and try again.
+                    backtrack = Some((star, eaten + 1));
+                    p = star + 1;
+                    n = eaten + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
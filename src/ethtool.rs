@@ -0,0 +1,236 @@
+// Linux ethtool ioctls. Drivers are free to not implement any of them,
+// EOPNOTSUPP is mapped to Ok(None).
+use libc as c;
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::ptr;
+
+const ETHTOOL_GRINGPARAM: u32 = 0x10;
+const ETHTOOL_GSTRINGS: u32 = 0x1b;
+const ETHTOOL_GSSET_INFO: u32 = 0x37;
+const ETHTOOL_GFEATURES: u32 = 0x3a;
+const ETHTOOL_GET_TS_INFO: u32 = 0x41;
+
+const ETH_SS_FEATURES: u32 = 4;
+const ETH_GSTRING_LEN: usize = 32;
+
+#[repr(C)]
+struct IfReq {
+    name: [c::c_char; c::IFNAMSIZ],
+    data: *mut c::c_void,
+    _pad: [u8; 24], // Rest of the ifr_ifru union.
+}
+
+// SIOCETHTOOL copies sizeof(struct ifreq) bytes from userspace.
+const _: () = assert!(mem::size_of::<IfReq>() >= mem::size_of::<c::ifreq>());
+
+#[repr(C)]
+#[derive(Default)]
+struct EthtoolRingParam {
+    cmd: u32,
+    rx_max_pending: u32,
+    rx_mini_max_pending: u32,
+    rx_jumbo_max_pending: u32,
+    tx_max_pending: u32,
+    rx_pending: u32,
+    rx_mini_pending: u32,
+    rx_jumbo_pending: u32,
+    tx_pending: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct EthtoolSsetInfo {
+    cmd: u32,
+    reserved: u32,
+    sset_mask: u64,
+    data: [u32; 1], // One count per bit in |sset_mask|.
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct EthtoolTsInfo {
+    cmd: u32,
+    so_timestamping: u32,
+    phc_index: i32,
+    tx_types: u32,
+    tx_reserved: [u32; 3],
+    rx_filters: u32,
+    rx_reserved: [u32; 3],
+}
+
+/// Packet timestamping capabilities, see [`timestamping()`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TsInfo {
+    /// Supported SOF_TIMESTAMPING_* flags.
+    pub so_timestamping: u32,
+    /// Index of the PTP hardware clock (/dev/ptpN), if any.
+    pub phc_index: Option<u32>,
+    /// Supported transmit modes, one bit per HWTSTAMP_TX_* value.
+    pub tx_types: u32,
+    /// Supported receive filters, one bit per HWTSTAMP_FILTER_* value.
+    pub rx_filters: u32,
+}
+
+/// Receive and transmit ring buffer sizes, see [`ring_params()`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RingParams {
+    /// Configured number of receive ring entries.
+    pub rx: u32,
+    /// Maximum number of receive ring entries.
+    pub rx_max: u32,
+    /// Configured number of transmit ring entries.
+    pub tx: u32,
+    /// Maximum number of transmit ring entries.
+    pub tx_max: u32,
+}
+
+/// Returns the ring buffer sizes of interface |name|, or None if the
+/// driver doesn't report them. Linux only.
+pub fn ring_params(name: &str) -> io::Result<Option<RingParams>> {
+    let mut data = EthtoolRingParam {
+        cmd: ETHTOOL_GRINGPARAM,
+        ..Default::default()
+    };
+
+    if !ioctl(name, &mut data as *mut _ as *mut _)? {
+        return Ok(None);
+    }
+
+    Ok(Some(RingParams {
+        rx: data.rx_pending,
+        rx_max: data.rx_max_pending,
+        tx: data.tx_pending,
+        tx_max: data.tx_max_pending,
+    }))
+}
+
+/// Returns the timestamping capabilities of interface |name|, or None if
+/// the driver doesn't report them. Linux only.
+pub fn timestamping(name: &str) -> io::Result<Option<TsInfo>> {
+    let mut data = EthtoolTsInfo {
+        cmd: ETHTOOL_GET_TS_INFO,
+        ..Default::default()
+    };
+
+    if !ioctl(name, &mut data as *mut _ as *mut _)? {
+        return Ok(None);
+    }
+
+    Ok(Some(TsInfo {
+        so_timestamping: data.so_timestamping,
+        phc_index: data.phc_index.try_into().ok(), // -1 if none.
+        tx_types: data.tx_types,
+        rx_filters: data.rx_filters,
+    }))
+}
+
+/// Returns the offload features of interface |name|, e.g., "tx-checksum-
+/// ipv4" or "rx-gro", and whether they are enabled. Empty if the driver
+/// doesn't report them. Linux only.
+pub fn offload_features(name: &str) -> io::Result<HashMap<String, bool>> {
+    let mut features = HashMap::new();
+
+    let mut info = EthtoolSsetInfo {
+        cmd: ETHTOOL_GSSET_INFO,
+        sset_mask: 1 << ETH_SS_FEATURES,
+        ..Default::default()
+    };
+
+    if !ioctl(name, &mut info as *mut _ as *mut _)? {
+        return Ok(features);
+    }
+
+    // The kernel clears the mask bits of the sets that it doesn't know.
+    if info.sset_mask == 0 {
+        return Ok(features);
+    }
+
+    let count = info.data[0] as usize;
+
+    // struct ethtool_gstrings, a header followed by |count| strings.
+    let mut strings = vec![0u32; 3 + count * ETH_GSTRING_LEN / 4];
+    strings[..3].copy_from_slice(&[ETHTOOL_GSTRINGS, ETH_SS_FEATURES, 0]);
+
+    if !ioctl(name, strings.as_mut_ptr() as *mut _)? {
+        return Ok(features);
+    }
+
+    // struct ethtool_gfeatures, a header followed by |blocks| blocks of
+    // available, requested, active and never_changed bitmasks.
+    let blocks = count.div_ceil(32);
+    let mut data = vec![0u32; 2 + 4 * blocks];
+    data[..2].copy_from_slice(&[ETHTOOL_GFEATURES, blocks as u32]);
+
+    if !ioctl(name, data.as_mut_ptr() as *mut _)? {
+        return Ok(features);
+    }
+
+    let count = count.min(strings[2] as usize);
+    let bytes = unsafe {
+        std::slice::from_raw_parts(
+            strings[3..].as_ptr() as *const u8,
+            count * ETH_GSTRING_LEN,
+        )
+    };
+
+    for (i, string) in bytes.chunks(ETH_GSTRING_LEN).enumerate() {
+        let len = string.iter().position(|&b| b == 0).unwrap_or(string.len());
+
+        // Unused feature bits have empty names.
+        if len == 0 {
+            continue;
+        }
+
+        let string = String::from_utf8_lossy(&string[..len]).into_owned();
+        let active = data[2 + 4 * (i / 32) + 2];
+        features.insert(string, 0 != active & (1 << (i % 32)));
+    }
+
+    Ok(features)
+}
+
+/// Issues a SIOCETHTOOL ioctl. Returns false if the operation is not
+/// supported by the driver.
+fn ioctl(name: &str, data: *mut c::c_void) -> io::Result<bool> {
+    let mut req = IfReq {
+        name: [0; c::IFNAMSIZ],
+        data,
+        _pad: [0; 24],
+    };
+
+    // Leave room for the nul byte.
+    if name.len() >= req.name.len() || name.as_bytes().contains(&0) {
+        let msg = "invalid interface name";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    }
+
+    unsafe {
+        ptr::copy_nonoverlapping(
+            name.as_ptr() as *const c::c_char,
+            req.name.as_mut_ptr(),
+            name.len(),
+        )
+    };
+
+    let fd =
+        unsafe { c::socket(c::AF_INET, c::SOCK_DGRAM | c::SOCK_CLOEXEC, 0) };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let rc = unsafe { c::ioctl(fd, c::SIOCETHTOOL as _, &mut req) };
+    let err = io::Error::last_os_error();
+
+    unsafe { c::close(fd) };
+
+    if rc == 0 {
+        Ok(true)
+    } else if err.raw_os_error() == Some(c::EOPNOTSUPP) {
+        Ok(false)
+    } else {
+        Err(err)
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+use std::ops::BitAnd;
+use std::ops::BitOr;
+use std::ops::BitOrAssign;
+
+/// Interface flags that mean the same thing on every platform, see
+/// [`crate::Interface::flags()`]. Combine with `|`, test with
+/// [`InterfaceFlags::contains()`].
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct InterfaceFlags(u32);
+
+impl InterfaceFlags {
+    /// Administratively up.
+    pub const UP: Self = InterfaceFlags(1);
+    /// Operationally up, i.e., ready to pass traffic.
+    pub const RUNNING: Self = InterfaceFlags(2);
+    /// Loopback interface.
+    pub const LOOPBACK: Self = InterfaceFlags(4);
+    /// Supports multicast.
+    pub const MULTICAST: Self = InterfaceFlags(8);
+    /// Supports broadcast.
+    pub const BROADCAST: Self = InterfaceFlags(16);
+    /// Point-to-point link, e.g., PPP or a VPN tunnel.
+    pub const POINTOPOINT: Self = InterfaceFlags(32);
+    /// Receives all packets on the link, not just the ones addressed to it.
+    pub const PROMISC: Self = InterfaceFlags(64);
+
+    const NAMES: [(Self, &'static str); 7] = [
+        (Self::UP, "UP"),
+        (Self::RUNNING, "RUNNING"),
+        (Self::LOOPBACK, "LOOPBACK"),
+        (Self::MULTICAST, "MULTICAST"),
+        (Self::BROADCAST, "BROADCAST"),
+        (Self::POINTOPOINT, "POINTOPOINT"),
+        (Self::PROMISC, "PROMISC"),
+    ];
+
+    pub const fn empty() -> Self {
+        InterfaceFlags(0)
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// True if all flags in |other| are set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Converts libc::IFF_* flags.
+    #[cfg(not(target_os = "windows"))]
+    pub(crate) fn from_raw(flags: u64) -> Self {
+        let table = [
+            (libc::IFF_UP, Self::UP),
+            (libc::IFF_RUNNING, Self::RUNNING),
+            (libc::IFF_LOOPBACK, Self::LOOPBACK),
+            (libc::IFF_MULTICAST, Self::MULTICAST),
+            (libc::IFF_BROADCAST, Self::BROADCAST),
+            (libc::IFF_POINTOPOINT, Self::POINTOPOINT),
+            (libc::IFF_PROMISC, Self::PROMISC),
+        ];
+
+        table
+            .into_iter()
+            .filter(|&(raw, _)| 0 != flags & raw as u64)
+            .fold(Self::empty(), |acc, (_, flag)| acc | flag)
+    }
+
+    /// Converts the IFF_* flags from ws2ipdef.h. Windows doesn't have
+    /// IFF_RUNNING but only sets IFF_UP when the interface is operational.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn from_raw(flags: u64) -> Self {
+        use crate::windows as w;
+
+        let table = [
+            (w::IFF_UP, Self::UP | Self::RUNNING),
+            (w::IFF_BROADCAST, Self::BROADCAST),
+            (w::IFF_LOOPBACK, Self::LOOPBACK),
+            (w::IFF_POINTTOPOINT, Self::POINTOPOINT),
+            (w::IFF_MULTICAST, Self::MULTICAST),
+        ];
+
+        table
+            .into_iter()
+            .filter(|&(raw, _)| 0 != flags & raw)
+            .fold(Self::empty(), |acc, (_, flag)| acc | flag)
+    }
+}
+
+impl BitOr for InterfaceFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        InterfaceFlags(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for InterfaceFlags {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for InterfaceFlags {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        InterfaceFlags(self.0 & other.0)
+    }
+}
+
+/// Prints e.g. `InterfaceFlags(UP | RUNNING)`.
+impl fmt::Debug for InterfaceFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InterfaceFlags(")?;
+
+        let names = Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| name);
+
+        for (i, name) in names.enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            f.write_str(name)?;
+        }
+
+        f.write_str(")")
+    }
+}
@@ -1,32 +1,427 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io;
 use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::net::SocketAddrV4;
+use std::net::SocketAddrV6;
+use std::net::UdpSocket;
+use std::ops::ControlFlow;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+mod cidr;
+mod device;
+mod dump;
+mod enumerator;
+mod flags;
+mod mac;
+mod snapshot;
+mod watch;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod ethtool;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod netlink;
+
+pub use cidr::Cidr;
+pub use device::devices;
+pub use device::Device;
+pub use dump::capture_to_writer;
+pub use dump::dump;
+pub use dump::load;
+pub use dump::restore_from_reader;
+pub use enumerator::enumerate;
+pub use enumerator::Enumerator;
+pub use flags::InterfaceFlags;
+pub use mac::MacAddr;
+pub use mac::MacAddrParseError;
+pub use snapshot::Change;
+pub use snapshot::Snapshot;
+pub use watch::watch;
+pub use watch::Event;
+pub use watch::Watcher;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::alias;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::bond_slaves;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::bridge_fdb;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::delegated_prefixes;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::set_alias;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::up_all_netns;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::FdbEntry;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::NetnsId;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use linux::SlaveInfo;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use ethtool::offload_features;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use ethtool::ring_params;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use ethtool::timestamping;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use ethtool::RingParams;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use ethtool::TsInfo;
 
 #[cfg(test)]
 mod test;
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Interface {
     name: String,
+    index: u32,
     flags: u64,
     mac: [u8; 6],
+    link_addr: Vec<u8>,
+    hw_type: u16,
     address: IpAddr,
     scope_id: Option<u32>,
     netmask: IpAddr,
+    secondary: bool,
+    metric: Option<u32>,
+    multicast: bool,
+    lease_expiry: Option<SystemTime>,
+    tentative: bool,
+    deprecated: bool,
+    destination: Option<IpAddr>,
+    broadcast: Option<IpAddr>,
+    speed: Option<u64>,
+    dormant: bool,
+    anycast: bool,
+    mtu: Option<u32>,
+    kernel_scope: Option<AddressScope>,
+    oper_status: OperStatus,
+}
+
+/// Compares everything but the lease expiry, which Windows reports relative
+/// to the current time, so that two listings of the same address are equal.
+impl PartialEq for Interface {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields() == other.fields()
+    }
+}
+
+impl Eq for Interface {}
+
+impl Hash for Interface {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fields().hash(state)
+    }
 }
 
 impl Interface {
+    /// Creates an interface from scratch, for use with [`Mock`].
+    /// Fields that aren't arguments are zero, false or None, except
+    /// that IPv6 addresses get a scope id of zero, like the real thing,
+    /// and IPv4 addresses the directed broadcast address of the subnet.
+    pub fn new(
+        name: impl Into<String>,
+        address: IpAddr,
+        netmask: IpAddr,
+    ) -> Self {
+        Interface {
+            name: name.into(),
+            index: 0,
+            flags: 0,
+            mac: [0; 6],
+            link_addr: Vec::new(),
+            hw_type: 0,
+            address,
+            scope_id: address.is_ipv6().then_some(0),
+            netmask,
+            secondary: false,
+            metric: None,
+            multicast: false,
+            lease_expiry: None,
+            tentative: false,
+            deprecated: false,
+            destination: None,
+            broadcast: directed_broadcast(address, netmask),
+            speed: None,
+            dormant: false,
+            anycast: false,
+            mtu: None,
+            kernel_scope: None,
+            oper_status: OperStatus::Unknown,
+        }
+    }
+
+    /// Sets the [`Interface::index()`].
+    pub fn with_index(mut self, index: u32) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Sets the [`Interface::raw_flags()`], the platform's IFF_* flags.
+    /// [`Interface::flags()`] is derived from them.
+    pub fn with_flags(mut self, flags: u64) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the [`Interface::mac()`] and, to the same six bytes, the
+    /// [`Interface::link_addr()`].
+    pub fn with_mac(mut self, mac: [u8; 6]) -> Self {
+        self.mac = mac;
+        self.link_addr = mac.to_vec();
+        self
+    }
+
+    /// Sets the [`Interface::scope_id()`]. [`Interface::new()`] sets it
+    /// to Some(0) for IPv6 addresses, use this for link-local ones.
+    pub fn with_scope_id(mut self, scope_id: Option<u32>) -> Self {
+        self.scope_id = scope_id;
+        self
+    }
+
     /// Interface name, e.g., "lo".
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    /// Interface flags. See libc::IFF_* flags.
-    pub fn flags(&self) -> u64 {
+    /// Interface index, the number that e.g. if_nametoindex(3) returns.
+    /// Zero when unknown.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Interface flags, the same on every platform. Windows doesn't have
+    /// a notion of promiscuous mode and never sets PROMISC.
+    pub fn flags(&self) -> InterfaceFlags {
+        #[cfg(not(target_os = "windows"))]
+        return InterfaceFlags::from_raw(self.flags);
+
+        #[cfg(target_os = "windows")]
+        return windows::flags(self);
+    }
+
+    /// Interface flags as reported by the operating system. See libc::IFF_*
+    /// flags. On Windows, the IFF_* flags from ws2ipdef.h, synthesized from
+    /// the adapter's operational status and type.
+    pub fn raw_flags(&self) -> u64 {
         self.flags
     }
 
     /// MAC address, a.k.a., link-layer address, a.k.a., physical address.
-    pub fn mac(&self) -> [u8; 6] {
-        self.mac
+    /// All zeroes if the interface doesn't have one, see
+    /// [`Interface::mac_opt()`].
+    pub fn mac(&self) -> MacAddr {
+        MacAddr::new(self.mac)
+    }
+
+    /// Like [`Interface::mac()`] but None if the interface doesn't have a
+    /// MAC address, e.g., tunnel interfaces and, except on Linux, loopback
+    /// interfaces. Linux reports an all-zero address for the loopback
+    /// interface and that is passed through as-is.
+    pub fn mac_opt(&self) -> Option<MacAddr> {
+        <[u8; 6]>::try_from(&self.link_addr[..])
+            .ok()
+            .map(MacAddr::new)
+    }
+
+    /// Link-layer address of any length, e.g., the 20 byte addresses of
+    /// InfiniBand interfaces that don't fit in a [`Interface::mac()`].
+    /// None if the interface doesn't have one.
+    pub fn link_addr(&self) -> Option<&[u8]> {
+        (!self.link_addr.is_empty()).then_some(&self.link_addr[..])
+    }
+
+    /// When the DHCP lease for this address expires. Windows only, None
+    /// on other platforms and for addresses that weren't configured with
+    /// DHCP or whose lease doesn't expire. Approximate, it can differ by
+    /// a second between listings, and therefore not compared by `==`.
+    pub fn lease_expiry(&self) -> Option<SystemTime> {
+        self.lease_expiry
+    }
+
+    /// True if the address isn't ready for use because duplicate address
+    /// detection (DAD) is still in progress or has failed. Sockets can't
+    /// be bound to tentative addresses. Always false on platforms other
+    /// than Linux and Windows.
+    pub fn is_tentative(&self) -> bool {
+        self.tentative
+    }
+
+    /// True if the address is deprecated, i.e., its preferred lifetime
+    /// has expired. Deprecated addresses still work for existing
+    /// connections but shouldn't be used for new ones. Always false on
+    /// platforms other than Linux and Windows.
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    /// True for IPv4 link-local (169.254.0.0/16) addresses, a.k.a. APIPA
+    /// addresses. Hosts assign themselves one when DHCP fails.
+    pub fn is_ipv4_linklocal(&self) -> bool {
+        match self.address {
+            IpAddr::V4(addr) => addr.is_link_local(),
+            IpAddr::V6(_) => false,
+        }
+    }
+
+    /// The address of the peer at the other end of a point-to-point link,
+    /// e.g., a PPP or VPN tunnel. None for other interfaces and on Windows.
+    pub fn destination(&self) -> Option<IpAddr> {
+        self.destination
+    }
+
+    /// The link MTU of the interface, the largest packet it can send
+    /// without fragmenting, including the IP header. None if unknown.
+    pub fn mtu(&self) -> Option<u32> {
+        self.mtu
+    }
+
+    /// True if the interface is configured for jumbo frames, i.e., if its
+    /// MTU is larger than the standard Ethernet MTU of 1500 bytes.
+    pub fn supports_jumbo_frames(&self) -> bool {
+        self.mtu.is_some_and(|mtu| mtu > 1500)
+    }
+
+    /// Link speed in bits per second, or None when unknown, e.g., because
+    /// the link is down or the driver doesn't report it.
+    pub fn speed(&self) -> Option<u64> {
+        self.speed
+    }
+
+    /// Link speed for display, e.g., "100 Mb/s", "2.5 Gb/s".
+    pub fn speed_string(&self) -> Option<String> {
+        let speed = self.speed?;
+
+        let (unit, suffix) = [
+            (1_000_000_000_000, "Tb/s"),
+            (1_000_000_000, "Gb/s"),
+            (1_000_000, "Mb/s"),
+            (1_000, "kb/s"),
+        ]
+        .into_iter()
+        .find(|&(unit, _)| speed >= unit)
+        .unwrap_or((1, "b/s"));
+
+        let value = format!("{:.1}", speed as f64 / unit as f64);
+        let value = value.strip_suffix(".0").unwrap_or(&value);
+
+        Some(format!("{value} {suffix}"))
+    }
+
+    /// The address scope that the kernel assigned to the address. Usually
+    /// but not always what the address range suggests, e.g., the kernel
+    /// considers 127.0.0.1 host-scoped and an address can be configured
+    /// with any scope. Linux only, None on other platforms.
+    pub fn kernel_scope(&self) -> Option<AddressScope> {
+        self.kernel_scope
+    }
+
+    /// True for anycast addresses, see [`anycast()`].
+    pub fn is_anycast(&self) -> bool {
+        self.anycast
+    }
+
+    /// True if the interface supports multicast, i.e., if it is okay to
+    /// join multicast groups on it.
+    pub fn supports_multicast(&self) -> bool {
+        self.multicast
+    }
+
+    /// True if the MAC address is locally administered rather than
+    /// assigned by the manufacturer, as is the case for the randomized
+    /// MAC addresses that modern operating systems use for Wi-Fi.
+    pub fn is_locally_administered(&self) -> bool {
+        0 != self.mac[0] & 2
+    }
+
+    /// True if the MAC address is a multicast (group) address.
+    pub fn is_multicast_mac(&self) -> bool {
+        0 != self.mac[0] & 1
+    }
+
+    /// The organizationally unique identifier (OUI), the first three
+    /// octets of the MAC address, that identifies the vendor. None when
+    /// there is no MAC address or when it is locally administered.
+    pub fn oui(&self) -> Option<[u8; 3]> {
+        let [b0, b1, b2, ..] = self.mac;
+        let ok = self.mac != [0; 6] && !self.is_locally_administered();
+        ok.then_some([b0, b1, b2])
+    }
+
+    /// True if this is an IPv6 address whose interface identifier (the
+    /// lower 64 bits) is the modified EUI-64 derived from the MAC address,
+    /// as is the case for SLAAC addresses without privacy extensions.
+    pub fn has_eui64_identifier(&self) -> bool {
+        match self.address {
+            IpAddr::V6(addr) if self.mac != [0; 6] => {
+                addr.octets()[8..] == eui64(self.mac)
+            }
+            _ => false,
+        }
+    }
+
+    /// True if the interface is in promiscuous mode, i.e., if it receives
+    /// all packets on the link, not just the ones addressed to it. Always
+    /// false on Windows.
+    pub fn is_promiscuous(&self) -> bool {
+        #[cfg(not(target_os = "windows"))]
+        return 0 != self.flags & libc::IFF_PROMISC as u64;
+
+        #[cfg(target_os = "windows")]
+        return false;
+    }
+
+    /// True if the interface is administratively up but operationally
+    /// dormant, i.e., waiting for an external event before it can pass
+    /// traffic, e.g., a Wi-Fi interface that isn't associated yet. Always
+    /// false on platforms other than Linux and Windows. On Windows, where
+    /// [`up()`] leaves out interfaces that aren't operational, only
+    /// [`all()`] returns dormant interfaces.
+    pub fn is_dormant(&self) -> bool {
+        self.dormant
+    }
+
+    /// Operational status of the interface, i.e., whether it can pass
+    /// traffic. Interfaces that [`up()`] returns are usually
+    /// [`OperStatus::Up`] but see [`all()`]. Linux reports
+    /// [`OperStatus::Unknown`] for interfaces that don't track their
+    /// status, like loopback and tun interfaces.
+    pub fn oper_status(&self) -> OperStatus {
+        self.oper_status
+    }
+
+    /// Link-layer hardware type as reported by the operating system, i.e.,
+    /// `ARPHRD_*` on Linux, `IFT_*` on the BSDs and `IF_TYPE_*` on Windows.
+    /// Zero when unknown.
+    pub fn hw_type(&self) -> u16 {
+        self.hw_type
+    }
+
+    /// What kind of interface this is, derived from [`Interface::hw_type()`]
+    /// and, on Linux, the interface's sysfs entry. Wi-Fi interfaces on the
+    /// BSDs and macOS report themselves as Ethernet.
+    pub fn kind(&self) -> InterfaceKind {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::kind(&self.name, self.hw_type);
+
+        #[cfg(all(
+            unix,
+            not(any(target_os = "android", target_os = "linux"))
+        ))]
+        return bsd::kind(self.hw_type);
+
+        #[cfg(target_os = "windows")]
+        return windows::kind(self.hw_type);
     }
 
     /// Interface address.
@@ -38,167 +433,2591 @@ impl Interface {
         &self.address
     }
 
-    /// IPv6 scope id or None.
+    /// IPv6 scope id or None. Note that this is Some(0) for IPv6 addresses
+    /// that aren't scoped, i.e., anything but link-local addresses. See
+    /// [`zone_id()`](Self::zone_id) for a less surprising alternative.
     pub fn scope_id(&self) -> Option<u32> {
         self.scope_id
     }
 
+    /// Zone index (the "eth0" in "fe80::1%eth0") for IPv6 link-local
+    /// addresses, None for all other addresses, IPv4 or IPv6.
+    pub fn zone_id(&self) -> Option<u32> {
+        let scoped = self.address.is_ipv6() && is_link_local(&self.address);
+        self.scope_id.filter(|_| scoped)
+    }
+
+    /// Name of the interface that the zone index refers to, e.g., "eth0"
+    /// for "fe80::1%eth0". Usually but not necessarily the name of this
+    /// interface. None for addresses without a zone index.
+    pub fn scope_name(&self) -> Option<String> {
+        let index = self.zone_id().filter(|&index| index != 0)?;
+
+        #[cfg(not(target_os = "windows"))]
+        return unix::index_to_name(index);
+
+        // Friendly names, not the names that if_indextoname() returns.
+        #[cfg(target_os = "windows")]
+        return up()
+            .ok()?
+            .find(|ifa| ifa.index == index)
+            .map(|ifa| ifa.name);
+    }
+
     pub fn netmask(&self) -> &IpAddr {
         &self.netmask
     }
 
+    /// Inverse of the netmask, e.g., 0.0.0.255 for a /24, as used by
+    /// Cisco-style access control lists.
+    pub fn wildcard_mask(&self) -> IpAddr {
+        match self.netmask {
+            IpAddr::V4(mask) => IpAddr::V4(!mask),
+            IpAddr::V6(mask) => IpAddr::V6(!mask),
+        }
+    }
+
+    /// True for secondary IPv4 addresses, i.e., addresses that fall in
+    /// the subnet of an earlier (primary) address on the same interface.
+    /// The kernel removes them when the primary address goes away, unless
+    /// promote_secondaries is enabled. Always false on platforms other
+    /// than Linux.
+    pub fn is_secondary(&self) -> bool {
+        self.secondary
+    }
+
+    /// Interface metric for the address family of this address, used
+    /// by Windows to rank routes. Always None on other platforms, where
+    /// the metric is a property of the route, not of the interface.
+    pub fn metric(&self) -> Option<u32> {
+        self.metric
+    }
+
     /// Caveat emptor: follows the Node.js "192.168.0.42/24" convention
     /// instead of the arguably more common "192.168.0.0/24" notation.
     pub fn cidr(&self) -> (&IpAddr, u8) {
+        (&self.address, self.prefix_len())
+    }
+
+    /// Like [`Interface::cidr()`] but as a [`Cidr`] that formats as
+    /// "192.168.0.42/24".
+    pub fn to_cidr(&self) -> Cidr {
+        Cidr::new(self.address, self.prefix_len())
+    }
+
+    /// Length of the network prefix, i.e., the number of one bits in the
+    /// netmask, e.g., 24 for 255.255.255.0.
+    pub fn prefix_len(&self) -> u8 {
         let range = match self.netmask {
             IpAddr::V4(addr) => u32::from_be_bytes(addr.octets()).count_ones(),
             IpAddr::V6(addr) => u128::from_be_bytes(addr.octets()).count_ones(),
         };
-        (&self.address, range as u8)
+        range as u8
+    }
+
+    /// Number of usable host addresses in the subnet. Excludes the network
+    /// and broadcast addresses of IPv4 subnets, except for /31 subnets
+    /// (RFC 3021) where both addresses are usable. IPv6 subnets don't
+    /// reserve addresses, /0 saturates at u128::MAX.
+    pub fn host_count(&self) -> u128 {
+        let prefix_len = self.prefix_len();
+
+        match self.address {
+            IpAddr::V4(_) => match prefix_len {
+                32 => 1,
+                31 => 2,
+                _ => (1 << (32 - prefix_len.min(32))) - 2,
+            },
+            IpAddr::V6(_) => {
+                let bits = 128 - u32::from(prefix_len.min(128));
+                1u128.checked_shl(bits).unwrap_or(u128::MAX)
+            }
+        }
+    }
+
+    /// The broadcast address of the interface, e.g., 192.168.1.255 for
+    /// 192.168.1.5/24. None for IPv6 addresses.
+    ///
+    /// On Unix, this is the address the kernel reports, and None for
+    /// interfaces that don't support broadcast, like loopback and
+    /// point-to-point interfaces. Windows doesn't report it, there it's
+    /// the directed broadcast address computed from the address and
+    /// netmask, and None for /31 and /32 subnets, which don't have one.
+    pub fn broadcast(&self) -> Option<IpAddr> {
+        self.broadcast
+    }
+
+    /// Terse view of the interface for logging. Displays as e.g.
+    /// "eth0#2 192.168.0.42/24".
+    pub fn summary(&self) -> Summary<'_> {
+        let (address, prefix_len) = self.cidr();
+        Summary {
+            name: &self.name,
+            index: self.index,
+            address,
+            prefix_len,
+        }
+    }
+
+    /// IPv6 router advertisement state of the interface. Linux only,
+    /// None on other platforms or when IPv6 is disabled.
+    pub fn ra_flags(&self) -> Option<RaFlags> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::ra_flags(self.index, &self.name);
+
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        return None;
+    }
+
+    /// Renders the address as a netplan configuration, e.g.:
+    ///
+    /// ```yaml
+    /// network:
+    ///   version: 2
+    ///   ethernets:
+    ///     eth0:
+    ///       addresses:
+    ///         - 192.168.0.42/24
+    /// ```
+    ///
+    /// Wi-Fi, VLAN, bridge and tunnel interfaces go in the wifis, vlans,
+    /// bridges and tunnels sections, everything else in ethernets.
+    ///
+    /// Caveat emptor: the stanza only has the address. Add what netplan
+    /// requires for the kind of device, e.g., the access points of a
+    /// Wi-Fi interface, and merge the stanzas of an interface's addresses
+    /// yourself.
+    pub fn to_netplan_yaml(&self) -> String {
+        let plain = |c: char| c.is_ascii_alphanumeric() || "._-".contains(c);
+
+        let name = if self.name.chars().all(plain) {
+            self.name.clone()
+        } else {
+            yaml_quote(&self.name)
+        };
+
+        let (address, prefix_len) = self.cidr();
+        let section = netplan_section(self.kind());
+
+        format!(
+            "network:\n  \
+               version: 2\n  \
+               {section}:\n    \
+                 {name}:\n      \
+                   addresses:\n        \
+                     - {address}/{prefix_len}\n"
+        )
+    }
+
+    /// Whether the interface forwards (routes) IPv4 and IPv6 packets.
+    /// Linux only, None on other platforms.
+    pub fn forwarding_enabled(&self) -> Option<(bool, bool)> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::forwarding_enabled(&self.name);
+
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        return None;
+    }
+
+    /// The IPv6 MTU of the interface, which is lower than the link MTU
+    /// when a router advertised a smaller MTU. Linux only, None on other
+    /// platforms.
+    pub fn ipv6_mtu(&self) -> Option<u32> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::ipv6_mtu(&self.name);
+
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        return None;
+    }
+
+    /// Signal strength in dBm of a Wi-Fi interface that is associated with
+    /// an access point, e.g., -40 for a strong signal, -80 for a weak one.
+    /// None for other interfaces. Linux and Windows only, None on other
+    /// platforms. Requires the `wifi` feature.
+    ///
+    /// Linux reads it from /proc/net/wireless, i.e., wireless extensions.
+    /// None when the driver reports the level on a relative scale rather
+    /// than in dBm.
+    #[cfg(feature = "wifi")]
+    pub fn signal_strength(&self) -> io::Result<Option<i32>> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::signal_strength(&self.name);
+
+        #[cfg(target_os = "windows")]
+        return windows::signal_strength(self.index);
+
+        #[cfg(all(
+            unix,
+            not(any(target_os = "android", target_os = "linux"))
+        ))]
+        return Ok(None);
+    }
+
+    /// The interface's use_tempaddr setting, which controls whether IPv6
+    /// temporary (privacy) addresses are generated: <= 0 means no, 1 means
+    /// yes but prefer public addresses, 2 means yes and prefer temporary
+    /// addresses. Linux only, None on other platforms.
+    pub fn tempaddr_policy(&self) -> Option<i32> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::tempaddr_policy(&self.name);
+
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        return None;
+    }
+
+    /// The id of the network namespace that the peer of the interface
+    /// lives in, e.g., for veth pairs that connect to a container. Only
+    /// set for interfaces that have such a peer. Linux only, None on
+    /// other platforms.
+    pub fn netns_id(&self) -> Option<i32> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::netns_id(self.index);
+
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        return None;
+    }
+
+    /// The interface group, as set with `ip link set dev eth0 group 42`.
+    /// Interfaces are in group 0 ("default") unless configured otherwise.
+    /// Linux only, None on other platforms.
+    pub fn group(&self) -> Option<u32> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        return linux::group(self.index);
+
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        return None;
+    }
+
+    /// Binds a UDP socket to this address and |port|. Takes care of
+    /// setting the scope id for IPv6 link-local addresses.
+    pub fn bind_udp(&self, port: u16) -> io::Result<UdpSocket> {
+        UdpSocket::bind(self.socket_addr(port))
+    }
+
+    /// Number of addresses currently configured on this interface,
+    /// including this one. Re-enumerates the interfaces to find out.
+    pub fn sibling_count(&self) -> io::Result<usize> {
+        Ok(up()?.filter(|ifa| ifa.is_sibling_of(self)).count())
+    }
+
+    fn is_sibling_of(&self, other: &Interface) -> bool {
+        match (self.index, other.index) {
+            (0, _) | (_, 0) => self.name == other.name,
+            (a, b) => a == b,
+        }
+    }
+
+    /// The fields that PartialEq and Hash look at.
+    fn fields(&self) -> impl Eq + Hash + '_ {
+        let Interface {
+            name,
+            index,
+            flags,
+            mac,
+            link_addr,
+            hw_type,
+            address,
+            scope_id,
+            netmask,
+            secondary,
+            metric,
+            multicast,
+            lease_expiry: _,
+            tentative,
+            deprecated,
+            destination,
+            broadcast,
+            speed,
+            dormant,
+            anycast,
+            mtu,
+            kernel_scope,
+            oper_status,
+        } = self;
+
+        let link = (name, index, flags, mac, link_addr, hw_type, speed);
+        let link = (link, dormant, mtu, multicast, oper_status);
+        let addr = (address, scope_id, netmask, secondary, metric);
+        let addr = (addr, tentative, deprecated, destination, broadcast);
+        let addr = (addr, anycast);
+        (link, addr, kernel_scope)
+    }
+
+    fn is_up(&self) -> bool {
+        #[cfg(not(target_os = "windows"))]
+        return 0 != self.flags & libc::IFF_UP as u64;
+
+        #[cfg(target_os = "windows")]
+        return self.oper_status == OperStatus::Up;
+    }
+
+    fn is_broadcast(&self) -> bool {
+        #[cfg(not(target_os = "windows"))]
+        return 0 != self.flags & libc::IFF_BROADCAST as u64;
+
+        #[cfg(target_os = "windows")]
+        if self.flags != 0 {
+            return 0 != self.flags & windows::IFF_BROADCAST;
+        }
+
+        // Everything but loopback (24), PPP (23) and tunnel (131) adapters.
+        #[cfg(target_os = "windows")]
+        return !matches!(self.hw_type, 23 | 24 | 131);
+    }
+
+    /// The network address of the subnet, e.g., 192.168.1.0 for
+    /// 192.168.1.5/24.
+    fn network(&self) -> IpAddr {
+        match (self.address, self.netmask) {
+            (IpAddr::V4(addr), IpAddr::V4(mask)) => IpAddr::V4(addr & mask),
+            (IpAddr::V6(addr), IpAddr::V6(mask)) => IpAddr::V6(addr & mask),
+            (addr, _) => addr, // Can't happen.
+        }
+    }
+
+    fn socket_addr(&self, port: u16) -> SocketAddr {
+        match self.address {
+            IpAddr::V4(addr) => SocketAddr::V4(SocketAddrV4::new(addr, port)),
+            IpAddr::V6(addr) => {
+                let scope_id = self.scope_id.unwrap_or(0);
+                SocketAddr::V6(SocketAddrV6::new(addr, port, 0, scope_id))
+            }
+        }
+    }
+
+    fn overlaps(&self, other: &Interface) -> bool {
+        let (a, m) = self.cidr();
+        let (b, n) = other.cidr();
+        same_subnet(a, b, m.min(n))
     }
 }
 
-#[cfg(target_os = "windows")]
-pub use windows::*;
+/// Address family.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Family {
+    V4,
+    V6,
+}
 
-#[cfg(not(target_os = "windows"))]
-pub use unix::*;
+impl Family {
+    pub(crate) fn of(addr: &IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(_) => Family::V4,
+            IpAddr::V6(_) => Family::V6,
+        }
+    }
+}
 
-#[cfg(target_os = "windows")]
-mod windows {
-    use super::Interface;
-    use std::io;
-    use std::net::IpAddr;
-    use std::net::Ipv4Addr;
-    use std::net::Ipv6Addr;
-    use std::ptr::null_mut;
-    use std::ptr::NonNull;
-    use winapi::shared::ifdef::IfOperStatusUp;
-    use winapi::shared::ws2def::SOCKADDR;
-    use winapi::shared::ws2def::SOCKADDR_IN;
-    use winapi::shared::ws2ipdef::SOCKADDR_IN6;
-    use winapi::um::iphlpapi::GetAdaptersAddresses;
-    use winapi::um::iptypes::GAA_FLAG_SKIP_ANYCAST;
-    use winapi::um::iptypes::GAA_FLAG_SKIP_DNS_SERVER;
-    use winapi::um::iptypes::GAA_FLAG_SKIP_MULTICAST;
-    use winapi::um::iptypes::IP_ADAPTER_ADDRESSES;
-    use winapi::um::iptypes::IP_ADAPTER_UNICAST_ADDRESS;
-    use winapi::um::winsock2::PF_INET;
-    use winapi::um::winsock2::PF_INET6;
-    use winapi::um::winsock2::PF_UNSPEC;
+/// Address scope, derived from the address range. See
+/// [`Interface::kernel_scope()`] for the kernel's classification.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Scope {
+    /// Publicly routable addresses.
+    Global,
+    /// Private IPv4 (RFC 1918) and unique local IPv6 (ULA) addresses.
+    Private,
+    /// Link-local addresses, 169.254.0.0/16 and fe80::/10.
+    LinkLocal,
+    /// Loopback addresses, 127.0.0.0/8 and ::1.
+    Loopback,
+}
+
+impl Scope {
+    pub(crate) fn of(addr: &IpAddr) -> Self {
+        if addr.is_loopback() {
+            Scope::Loopback
+        } else if is_link_local(addr) {
+            Scope::LinkLocal
+        } else if scope_rank(addr) == 1 {
+            Scope::Private
+        } else {
+            Scope::Global
+        }
+    }
+}
+
+/// See [`listen_addresses()`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ListenOptions {
+    /// Port number of the returned socket addresses.
+    pub port: u16,
+    /// Only return addresses of this family. None means both families.
+    pub family: Option<Family>,
+    /// Include loopback addresses.
+    pub loopback: bool,
+    /// Include link-local addresses.
+    pub link_local: bool,
+}
+
+/// See [`Interface::ra_flags()`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RaFlags {
+    /// Managed address configuration (M) flag from the last router
+    /// advertisement, i.e., addresses are available via DHCPv6.
+    pub managed: bool,
+    /// Other configuration (O) flag from the last router advertisement,
+    /// i.e., other configuration (DNS, etc.) is available via DHCPv6.
+    pub other: bool,
+    /// Whether stateless address autoconfiguration (SLAAC) is enabled.
+    pub autoconf: bool,
+}
+
+/// Address scope as classified by the kernel, see
+/// [`Interface::kernel_scope()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum AddressScope {
+    /// Valid everywhere (RT_SCOPE_UNIVERSE).
+    Global,
+    /// Valid within the site, deprecated IPv6 site-local addresses.
+    Site,
+    /// Valid on the link only, e.g., link-local addresses.
+    Link,
+    /// Valid on the host only, e.g., loopback addresses.
+    Host,
+    /// Not valid anywhere.
+    Nowhere,
+    /// User-defined scope, see /etc/iproute2/rt_scopes.
+    Other(u8),
+}
+
+impl From<u8> for AddressScope {
+    fn from(scope: u8) -> Self {
+        match scope {
+            0 => Self::Global,
+            200 => Self::Site,
+            253 => Self::Link,
+            254 => Self::Host,
+            255 => Self::Nowhere,
+            scope => Self::Other(scope),
+        }
+    }
+}
+
+impl From<AddressScope> for u8 {
+    fn from(scope: AddressScope) -> Self {
+        match scope {
+            AddressScope::Global => 0,
+            AddressScope::Site => 200,
+            AddressScope::Link => 253,
+            AddressScope::Host => 254,
+            AddressScope::Nowhere => 255,
+            AddressScope::Other(scope) => scope,
+        }
+    }
+}
+
+/// See [`Interface::kind()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum InterfaceKind {
+    /// Loopback interface.
+    Loopback,
+    /// Wired Ethernet, or anything that looks like it, e.g., veth pairs.
+    Ethernet,
+    /// IEEE 802.11 wireless.
+    Wifi,
+    /// IP tunnel, e.g., GRE, IP-in-IP, 6to4, WireGuard or tun devices.
+    Tunnel,
+    /// Software bridge.
+    Bridge,
+    /// IEEE 802.1Q VLAN.
+    Vlan,
+    /// Cellular (mobile broadband) modem.
+    Cellular,
+    /// Anything else, see [`Interface::hw_type()`] for the raw type.
+    Other,
+}
+
+/// Operational status of an interface, see [`Interface::oper_status()`].
+/// Follows the ifOperStatus values from RFC 2863.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum OperStatus {
+    /// Ready to pass packets.
+    Up,
+    /// Not ready to pass packets, e.g., administratively down.
+    Down,
+    /// In test mode.
+    Testing,
+    /// Status can't be determined.
+    Unknown,
+    /// Waiting for an external event, e.g., Wi-Fi association.
+    Dormant,
+    /// A hardware component is missing.
+    NotPresent,
+    /// Down because a lower-layer interface is down, e.g., no carrier.
+    LowerLayerDown,
+}
+
+impl OperStatus {
+    /// Converts an RFC 2863 ifOperStatus value, which Windows also uses.
+    pub(crate) fn from_rfc2863(status: u32) -> Self {
+        match status {
+            1 => Self::Up,
+            2 => Self::Down,
+            3 => Self::Testing,
+            5 => Self::Dormant,
+            6 => Self::NotPresent,
+            7 => Self::LowerLayerDown,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub(crate) fn to_rfc2863(self) -> u32 {
+        match self {
+            Self::Up => 1,
+            Self::Down => 2,
+            Self::Testing => 3,
+            Self::Unknown => 4,
+            Self::Dormant => 5,
+            Self::NotPresent => 6,
+            Self::LowerLayerDown => 7,
+        }
+    }
+}
+
+/// Why [`up_verbose()`] skipped an entry. Carries the interface name.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// Entry has no address.
+    NoAddress(String),
+    /// Link-layer entry. Its details are merged into the address entries.
+    LinkLayer(String),
+    /// Address is not IPv4 or IPv6. Carries the address family.
+    UnsupportedFamily(String, u16),
+    /// Interface is not operational. Windows only, Unix reports interfaces
+    /// that are down.
+    NotUp(String),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAddress(name) => write!(f, "{name}: no address"),
+            Self::LinkLayer(name) => write!(f, "{name}: link-layer entry"),
+            Self::UnsupportedFamily(name, family) => {
+                write!(f, "{name}: unsupported address family {family}")
+            }
+            Self::NotUp(name) => write!(f, "{name}: not up"),
+        }
+    }
+}
+
+impl std::error::Error for SkipReason {}
+
+/// See [`Interface::summary()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Summary<'a> {
+    name: &'a str,
+    index: u32,
+    address: &'a IpAddr,
+    prefix_len: u8,
+}
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Summary {
+            name,
+            index,
+            address,
+            prefix_len,
+        } = self;
+        write!(f, "{name}#{index} {address}/{prefix_len}")
+    }
+}
+
+/// Source of interfaces. Code that is generic over the provider can be
+/// tested with a [`Mock`] instead of the interfaces of the machine
+/// it happens to run on.
+pub trait Provider {
+    fn enumerate(&self) -> io::Result<Vec<Interface>>;
+}
+
+/// The operating system, i.e., [`up()`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Os;
+
+impl Provider for Os {
+    fn enumerate(&self) -> io::Result<Vec<Interface>> {
+        Ok(up()?.collect())
+    }
+}
+
+/// Fixed list of interfaces, for testing.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Mock(pub Vec<Interface>);
+
+impl Provider for Mock {
+    fn enumerate(&self) -> io::Result<Vec<Interface>> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Calls |f| for every interface that [`up()`] produces until |f| returns
+/// ControlFlow::Break. Streams the interfaces instead of collecting them
+/// into a Vec, and releases the operating system's list when it returns.
+pub fn for_each_interface(
+    mut f: impl FnMut(&Interface) -> ControlFlow<()>,
+) -> io::Result<()> {
+    for ifa in up()? {
+        if f(&ifa).is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the address that is the best choice as the source address for
+/// outbound connections, or None if there is no suitable address. Prefers,
+/// in order: global over private over link-local addresses, a lower
+/// interface metric (Windows only) and primary over secondary addresses.
+/// Loopback addresses and interfaces that aren't up are never selected.
+pub fn primary_source(family: Family) -> io::Result<Option<IpAddr>> {
+    let addr = up()?
+        .filter(|ifa| Family::of(&ifa.address) == family)
+        .filter(|ifa| ifa.is_up())
+        .filter(|ifa| !ifa.address.is_loopback())
+        .filter(|ifa| !ifa.address.is_unspecified())
+        .min_by_key(|ifa| {
+            let metric = ifa.metric.unwrap_or(u32::MAX);
+            (scope_rank(&ifa.address), metric, ifa.secondary)
+        })
+        .map(|ifa| ifa.address);
+    Ok(addr)
+}
+
+/// True if any interface that is up has a global or unique local (ULA)
+/// IPv6 address, i.e., if it makes sense to attempt IPv6 connections.
+pub fn has_ipv6() -> io::Result<bool> {
+    let ok = up()?.any(|ifa| {
+        ifa.address.is_ipv6()
+            && ifa.is_up()
+            && !ifa.address.is_loopback()
+            && !ifa.address.is_unspecified()
+            && !is_link_local(&ifa.address)
+    });
+    Ok(ok)
+}
+
+/// True if any interface that is up has an IPv4 link-local (APIPA)
+/// address, a strong sign that DHCP failed on that interface.
+pub fn has_apipa() -> io::Result<bool> {
+    Ok(up()?.any(|ifa| ifa.is_up() && ifa.is_ipv4_linklocal()))
+}
+
+/// Returns the interfaces that are carrying traffic, i.e., whose byte
+/// counters increased in the |window| this function sleeps for. Longer
+/// windows give more accurate results for links that are mostly idle.
+pub fn active_links(window: Duration) -> io::Result<Vec<Interface>> {
+    let ifas = up()?.collect::<Vec<_>>();
+    let before = byte_counts(&ifas);
+    let before = ifas.into_iter().map(|ifa| ifa.name).zip(before);
+    let before = before.collect::<Vec<_>>();
+
+    thread::sleep(window);
+
+    let ifas = up()?.collect::<Vec<_>>();
+    let after = byte_counts(&ifas);
+
+    let active = ifas
+        .into_iter()
+        .zip(after)
+        .filter(|(ifa, after)| {
+            let Some(after) = *after else {
+                return false;
+            };
+
+            before
+                .iter()
+                .find(|(name, _)| *name == ifa.name)
+                .and_then(|&(_, before)| before)
+                .is_some_and(|before| after > before)
+        })
+        .map(|(ifa, _)| ifa)
+        .collect();
+
+    Ok(active)
+}
+
+/// Total number of bytes received and sent by each of |ifas|, or None
+/// if the operating system doesn't report it. Takes one sample for all
+/// interfaces.
+fn byte_counts(ifas: &[Interface]) -> Vec<Option<u64>> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    return ifas
+        .iter()
+        .map(|ifa| linux::byte_count(&ifa.name))
+        .collect();
+
+    #[cfg(all(unix, not(any(target_os = "android", target_os = "linux"))))]
+    return {
+        let counts = bsd::byte_counts();
+        ifas.iter()
+            .map(|ifa| counts.get(&ifa.name).copied())
+            .collect()
+    };
+
+    #[cfg(target_os = "windows")]
+    return ifas
+        .iter()
+        .map(|ifa| windows::byte_count(ifa.index))
+        .collect();
+}
+
+/// Returns the interfaces grouped by MAC address. Bond and bridge members
+/// often share a MAC address with their master, making this a heuristic
+/// for finding them. Interfaces without a MAC address are left out.
+pub fn group_by_mac() -> io::Result<HashMap<MacAddr, Vec<Interface>>> {
+    let mut groups: HashMap<_, Vec<_>> = HashMap::new();
+
+    for ifa in up()?.filter(|ifa| !ifa.mac().is_zero()) {
+        groups.entry(ifa.mac()).or_default().push(ifa);
+    }
+
+    Ok(groups)
+}
+
+/// Returns the interfaces grouped by subnet, keyed by network address and
+/// prefix length, e.g., (192.168.1.0, 24). Interfaces in the same group
+/// are attached to the same network, except for link-local subnets: every
+/// link has its own but they all share the fe80::/64 key.
+pub fn group_by_subnet() -> io::Result<HashMap<(IpAddr, u8), Vec<Interface>>> {
+    let mut groups: HashMap<_, Vec<_>> = HashMap::new();
+
+    for ifa in up()? {
+        let key = (ifa.network(), ifa.prefix_len());
+        groups.entry(key).or_default().push(ifa);
+    }
+
+    Ok(groups)
+}
+
+/// True if no interface that is up has |public| as its address, i.e., if
+/// the host reaches the internet through network address translation.
+/// |public| is the host's public address as seen from the outside, e.g.,
+/// the reflexive address that a STUN server reported.
+pub fn is_behind_nat(public: &IpAddr) -> io::Result<bool> {
+    Ok(!up()?.any(|ifa| ifa.is_up() && ifa.address == *public))
+}
+
+/// Returns the pairs of interfaces whose subnets overlap, a common cause
+/// of routing trouble. Addresses on the same interface are not compared
+/// against each other and link-local subnets are ignored because those
+/// are scoped to a single link by definition.
+pub fn overlapping_subnets() -> io::Result<Vec<(Interface, Interface)>> {
+    let ifas = up()?
+        .filter(|ifa| !is_link_local(ifa.address()))
+        .collect::<Vec<_>>();
+
+    let mut pairs = Vec::new();
+
+    for (i, a) in ifas.iter().enumerate() {
+        for b in &ifas[i + 1..] {
+            if a.name != b.name && a.overlaps(b) {
+                pairs.push((a.clone(), b.clone()));
+            }
+        }
+    }
+
+    Ok(pairs)
+}
+
+/// Returns the addresses of the interface with index |index|.
+pub fn addresses_of_index(index: u32) -> io::Result<Vec<IpAddr>> {
+    let addrs = up()?
+        .filter(|ifa| ifa.index == index)
+        .map(|ifa| ifa.address)
+        .collect();
+    Ok(addrs)
+}
+
+/// Returns the socket addresses a server should bind to when it wants to
+/// listen on every local address, one socket per address. IPv6 link-local
+/// addresses come with the scope id that binding to them requires.
+/// Addresses of interfaces that aren't up and tentative addresses are
+/// left out.
+pub fn listen_addresses(opts: ListenOptions) -> io::Result<Vec<SocketAddr>> {
+    let addrs = up()?
+        .filter(|ifa| ifa.is_up() && !ifa.tentative)
+        .filter(|ifa| !ifa.address.is_unspecified())
+        .filter(|ifa| {
+            opts.family.is_none()
+                || opts.family == Some(Family::of(&ifa.address))
+        })
+        .filter(|ifa| opts.loopback || !ifa.address.is_loopback())
+        .filter(|ifa| opts.link_local || !is_link_local(&ifa.address))
+        .map(|ifa| ifa.socket_addr(opts.port))
+        .collect();
+    Ok(addrs)
+}
+
+/// Like [`up()`] but only IPv4 addresses. Cheaper than filtering the
+/// output of [`up()`] because the filtering happens earlier, in the case
+/// of Windows in the operating system.
+pub fn up_v4() -> io::Result<Up> {
+    up_with(false, Some(Family::V4))
+}
+
+/// Like [`up()`] but only IPv6 addresses, see [`up_v4()`].
+pub fn up_v6() -> io::Result<Up> {
+    up_with(false, Some(Family::V6))
+}
+
+/// Returns the broadcast address and |port| of every IPv4 interface that is
+/// up and supports broadcast, for sending service discovery packets to.
+/// The socket the packets are sent from needs SO_BROADCAST.
+pub fn broadcast_targets(port: u16) -> io::Result<Vec<SocketAddr>> {
+    let targets = up()?
+        .filter(|ifa| ifa.is_up() && ifa.is_broadcast())
+        .filter_map(|ifa| ifa.broadcast())
+        .map(|addr| SocketAddr::new(addr, port))
+        .collect();
+    Ok(targets)
+}
+
+/// Returns the point-to-point interfaces, e.g., PPP links and some VPN
+/// tunnels, together with the address of the peer at the other end.
+/// Point-to-point interfaces without a peer address are left out.
+pub fn point_to_point_links() -> io::Result<Vec<(Interface, IpAddr)>> {
+    let links = up()?
+        .filter_map(|ifa| ifa.destination().map(|peer| (ifa, peer)))
+        .collect();
+    Ok(links)
+}
+
+/// Returns the addresses of interface |name| that sockets can be bound to,
+/// i.e., the preferred addresses that passed duplicate address detection.
+/// Tentative addresses, addresses that are duplicates of another host's
+/// address and deprecated addresses, which are on their way out and
+/// shouldn't be used for new connections, are left out.
+pub fn bindable_addresses(name: &str) -> io::Result<Vec<IpAddr>> {
+    let addrs = up()?
+        .filter(|ifa| ifa.name == name)
+        .filter(|ifa| !ifa.tentative && !ifa.deprecated)
+        .map(|ifa| ifa.address)
+        .collect();
+    Ok(addrs)
+}
+
+/// Returns the anycast addresses of all interfaces, e.g., the IPv6
+/// subnet-router anycast addresses of a router. [`up()`] doesn't report
+/// them. Linux and Windows only, empty on other platforms.
+pub fn anycast() -> io::Result<Vec<Interface>> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    return linux::anycast();
+
+    #[cfg(target_os = "windows")]
+    return windows::anycast();
+
+    #[cfg(all(unix, not(any(target_os = "android", target_os = "linux"))))]
+    return Ok(Vec::new());
+}
+
+/// Returns the addresses of all interfaces except interface |name|, e.g.,
+/// every local address but those of a VPN tunnel.
+pub fn addresses_excluding(name: &str) -> io::Result<Vec<IpAddr>> {
+    let addrs = up()?
+        .filter(|ifa| ifa.name != name)
+        .map(|ifa| ifa.address)
+        .collect();
+    Ok(addrs)
+}
+
+/// Returns the interface that has |addr| as its address.
+pub fn owner_of(addr: &IpAddr) -> io::Result<Option<Interface>> {
+    Ok(up()?.find(|ifa| ifa.address == *addr))
+}
+
+/// Returns the interface that |socket| is bound to, i.e., the interface
+/// that owns the socket's local address. For a connected socket, that is
+/// the interface traffic to the peer egresses on. Returns None when the
+/// socket is bound to the wildcard address and isn't connected.
+pub fn of_udp_socket(socket: &UdpSocket) -> io::Result<Option<Interface>> {
+    let local = socket.local_addr()?;
+    let addr = local.ip();
+
+    let scope_id = match local {
+        SocketAddr::V4(_) => None,
+        SocketAddr::V6(local) if local.scope_id() == 0 => None,
+        SocketAddr::V6(local) => Some(local.scope_id()),
+    };
+
+    // Link-local addresses are only unique when qualified with a scope id.
+    let ifa = up()?.find(|ifa| {
+        ifa.address == addr && (scope_id.is_none() || ifa.scope_id == scope_id)
+    });
+
+    Ok(ifa)
+}
+
+/// Returns the interface whose address is |addr| or, failing that, the
+/// interface whose subnet contains |addr|, the most specific subnet if
+/// there are several. Link-local subnets are ambiguous, every link has
+/// one, and are only matched by address.
+pub fn find_by_address(addr: IpAddr) -> io::Result<Option<Interface>> {
+    let mut ifas = up()?.collect::<Vec<_>>();
+
+    let exact = ifas.iter().position(|ifa| ifa.address == addr);
+
+    let i = exact.or_else(|| {
+        ifas.iter()
+            .enumerate()
+            .filter(|(_, ifa)| !is_link_local(&ifa.address))
+            .filter(|(_, ifa)| {
+                same_subnet(&ifa.address, &addr, ifa.prefix_len())
+            })
+            .max_by_key(|(_, ifa)| ifa.prefix_len())
+            .map(|(i, _)| i)
+    });
+
+    Ok(i.map(|i| ifas.swap_remove(i)))
+}
+
+/// Returns the primary IPv4 address of interface |name|, or None if the
+/// interface is down or doesn't have an IPv4 address. Errors when there
+/// is no interface by that name. Faster than [`by_name()`] because it
+/// queries the interface directly, with ioctls on Linux and the BSDs and
+/// with GetIfEntry2() and GetIpInterfaceEntry() on Windows, instead of
+/// walking the list of every address on the system, but that means it
+/// doesn't fill in details that those don't report, like the link speed,
+/// or the MAC address on the BSDs.
+pub fn query(name: &str) -> io::Result<Option<Interface>> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    return linux::query(name);
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    ))]
+    return bsd::query(name);
+
+    #[cfg(target_os = "windows")]
+    return windows::query(name);
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "windows",
+    )))]
+    {
+        let ifas = by_name(name)?;
+
+        if ifas.is_empty() && !names()?.iter().any(|(_, other)| other == name) {
+            let msg = "no such interface";
+            return Err(io::Error::new(io::ErrorKind::NotFound, msg));
+        }
+
+        Ok(ifas.into_iter().find(|ifa| ifa.address.is_ipv4()))
+    }
+}
+
+/// Returns the interface that traffic to the internet egresses on, or None
+/// if there is no route to the internet. Asks the operating system which
+/// local address it would use to reach a well-known public address, IPv4
+/// first, then IPv6. No packets are sent.
+pub fn internet_facing() -> io::Result<Option<Interface>> {
+    let probes: [(SocketAddr, SocketAddr); 2] = [
+        (
+            (IpAddr::from([0, 0, 0, 0]), 0).into(),
+            (IpAddr::from([8, 8, 8, 8]), 53).into(),
+        ),
+        (
+            (IpAddr::from([0u16; 8]), 0).into(),
+            (
+                IpAddr::from([0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888]),
+                53,
+            )
+                .into(),
+        ),
+    ];
+
+    for (local, remote) in probes {
+        // Fails when the address family is disabled.
+        let Ok(socket) = UdpSocket::bind(local) else {
+            continue;
+        };
+
+        // Fails with ENETUNREACH when there is no route.
+        if socket.connect(remote).is_ok() {
+            if let Some(ifa) = of_udp_socket(&socket)? {
+                return Ok(Some(ifa));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns every interface paired with whether |probe| is reachable from
+/// its address. Reachable means that a UDP socket bound to the interface's
+/// address can be connected to |probe|, i.e., that there is a route. No
+/// packets are sent. Interfaces of the other address family are never
+/// reachable.
+pub fn with_reachability(probe: IpAddr) -> io::Result<Vec<(Interface, bool)>> {
+    let reachable = |ifa: &Interface| -> io::Result<()> {
+        let socket = UdpSocket::bind(ifa.socket_addr(0))?;
+        let probe = match probe {
+            // Link-local addresses are only reachable with a scope id.
+            IpAddr::V6(addr) if is_link_local(&probe) => {
+                let scope_id = ifa.scope_id.unwrap_or(0);
+                SocketAddr::V6(SocketAddrV6::new(addr, 9, 0, scope_id))
+            }
+            _ => SocketAddr::new(probe, 9), // Discard protocol.
+        };
+        socket.connect(probe)
+    };
+
+    let ifas = up()?
+        .map(|ifa| {
+            let same_family = ifa.address.is_ipv4() == probe.is_ipv4();
+            let ok = same_family && reachable(&ifa).is_ok();
+            (ifa, ok)
+        })
+        .collect();
+
+    Ok(ifas)
+}
+
+/// Waits until interface |name| is up and has at least one address, or
+/// until |timeout| elapses. Returns the first address that shows up or
+/// None on timeout.
+pub fn wait_for(
+    name: &str,
+    timeout: Duration,
+) -> io::Result<Option<Interface>> {
+    let start = Instant::now();
+    // Subscribe before listing, or changes in between go unnoticed.
+    let mut watcher = watch()?;
+
+    loop {
+        let found = up()?.find(|ifa| ifa.name == name && ifa.is_up());
+
+        if found.is_some() {
+            return Ok(found);
+        }
+
+        let elapsed = start.elapsed();
+
+        if elapsed >= timeout {
+            return Ok(None);
+        }
+
+        // Any change can be the one, look again.
+        if watcher.next_timeout(timeout - elapsed)?.is_none() {
+            return Ok(None);
+        }
+    }
+}
+
+/// Modified EUI-64 interface identifier, see RFC 4291, appendix A.
+fn eui64(mac: [u8; 6]) -> [u8; 8] {
+    let [b0, b1, b2, b3, b4, b5] = mac;
+    [b0 ^ 2, b1, b2, 0xff, 0xfe, b3, b4, b5]
+}
+
+/// Lower is more widely reachable: global, private, link-local.
+pub(crate) fn scope_rank(addr: &IpAddr) -> u8 {
+    let private = match addr {
+        IpAddr::V4(addr) => addr.is_private(),
+        IpAddr::V6(addr) => addr.segments()[0] & 0xfe00 == 0xfc00, // ULA
+    };
+
+    if is_link_local(addr) {
+        2
+    } else if private {
+        1
+    } else {
+        0
+    }
+}
+
+fn is_link_local(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => addr.is_link_local(),
+        IpAddr::V6(addr) => addr.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+/// True if the first |prefix| bits of |a| and |b| are the same.
+fn same_subnet(a: &IpAddr, b: &IpAddr, prefix: u8) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            let mask = (!0u32).checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(*a) & mask == u32::from(*b) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => {
+            let mask = (!0u128).checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(*a) & mask == u128::from(*b) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Renders |s| as a YAML double-quoted scalar.
+fn yaml_quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\x{:02x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// The netplan section that interfaces of |kind| are configured in.
+fn netplan_section(kind: InterfaceKind) -> &'static str {
+    match kind {
+        InterfaceKind::Wifi => "wifis",
+        InterfaceKind::Vlan => "vlans",
+        InterfaceKind::Bridge => "bridges",
+        InterfaceKind::Tunnel => "tunnels",
+        _ => "ethernets",
+    }
+}
+
+/// The directed broadcast address of the subnet, e.g., 192.168.1.255 for
+/// 192.168.1.5/24. None for IPv6 and for /31 and /32 subnets.
+fn directed_broadcast(address: IpAddr, netmask: IpAddr) -> Option<IpAddr> {
+    let (IpAddr::V4(addr), IpAddr::V4(mask)) = (address, netmask) else {
+        return None;
+    };
+
+    if u32::from(mask).count_ones() >= 31 {
+        return None;
+    }
+
+    Some(IpAddr::V4(addr | !mask))
+}
+
+#[cfg(target_os = "windows")]
+pub use windows::*;
+
+#[cfg(not(target_os = "windows"))]
+pub use unix::*;
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::Family;
+    use super::Interface;
+    use super::InterfaceFlags;
+    use super::InterfaceKind;
+    use super::OperStatus;
+    use super::SkipReason;
+    use crate::snapshot::buffers;
+    use crate::snapshot::Scratch;
+    use std::io;
+    use std::mem;
+    use std::net::IpAddr;
+    use std::net::Ipv4Addr;
+    use std::net::Ipv6Addr;
+    use std::ptr::null;
+    use std::ptr::null_mut;
+    use std::ptr::NonNull;
+    use std::sync::Mutex;
+    use std::sync::PoisonError;
+    use std::time::Duration;
+    use std::time::SystemTime;
+    use winapi::shared::ifdef::IfOperStatusDormant;
+    use winapi::shared::ifdef::IfOperStatusUp;
+    use winapi::shared::ifdef::NET_LUID;
+    use winapi::shared::minwindef::FALSE;
+    use winapi::shared::minwindef::TRUE;
+    use winapi::shared::netioapi::CancelMibChangeNotify2;
+    use winapi::shared::netioapi::ConvertInterfaceAliasToLuid;
+    use winapi::shared::netioapi::FreeMibTable;
+    use winapi::shared::netioapi::GetIfEntry2;
+    use winapi::shared::netioapi::GetIpInterfaceEntry;
+    use winapi::shared::netioapi::GetUnicastIpAddressTable;
+    use winapi::shared::netioapi::InitializeIpInterfaceEntry;
+    use winapi::shared::netioapi::MibAddInstance;
+    use winapi::shared::netioapi::MibDeleteInstance;
+    use winapi::shared::netioapi::NotifyIpInterfaceChange;
+    use winapi::shared::netioapi::NotifyUnicastIpAddressChange;
+    use winapi::shared::netioapi::MIB_IF_ROW2;
+    use winapi::shared::netioapi::MIB_IPINTERFACE_ROW;
+    use winapi::shared::netioapi::MIB_NOTIFICATION_TYPE;
+    use winapi::shared::netioapi::MIB_UNICASTIPADDRESS_ROW;
+    use winapi::shared::nldef::IpDadStateDeprecated;
+    use winapi::shared::nldef::IpDadStatePreferred;
+    use winapi::shared::nldef::IpSuffixOriginDhcp;
+    use winapi::shared::ntdef::HANDLE;
+    use winapi::shared::winerror::ERROR_BUFFER_OVERFLOW;
+    use winapi::shared::winerror::NO_ERROR;
+    use winapi::shared::winerror::WAIT_TIMEOUT;
+    use winapi::shared::ws2def::SOCKADDR;
+    use winapi::shared::ws2def::SOCKADDR_IN;
+    use winapi::shared::ws2ipdef::SOCKADDR_IN6;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::iphlpapi::GetAdaptersAddresses;
+    use winapi::um::iptypes::GAA_FLAG_INCLUDE_ALL_INTERFACES;
+    use winapi::um::iptypes::GAA_FLAG_SKIP_ANYCAST;
+    use winapi::um::iptypes::GAA_FLAG_SKIP_DNS_SERVER;
+    use winapi::um::iptypes::GAA_FLAG_SKIP_MULTICAST;
+    use winapi::um::iptypes::GAA_FLAG_SKIP_UNICAST;
+    use winapi::um::iptypes::IP_ADAPTER_ADDRESSES;
+    use winapi::um::iptypes::IP_ADAPTER_NO_MULTICAST;
+    use winapi::um::iptypes::IP_ADAPTER_UNICAST_ADDRESS;
+    use winapi::um::synchapi::CreateEventW;
+    use winapi::um::synchapi::ResetEvent;
+    use winapi::um::synchapi::SetEvent;
+    use winapi::um::synchapi::WaitForSingleObject;
+    use winapi::um::winbase::WAIT_OBJECT_0;
+    use winapi::um::winsock2::PF_INET;
+    use winapi::um::winsock2::PF_INET6;
+    use winapi::um::winsock2::PF_UNSPEC;
+
+    const INFINITE: u32 = !0;
+
+    /// Returns an iterator that produces the addresses of the adapters
+    /// that are operational, see [`Interface::oper_status()`].
+    pub fn up() -> io::Result<Up> {
+        up_with(false, None)
+    }
+
+    /// Like [`up()`] but includes interfaces that are down, e.g.,
+    /// disconnected or disabled adapters. See [`Interface::oper_status()`].
+    /// On Unix, up() doesn't leave those out and all() is the same.
+    pub fn all() -> io::Result<Up> {
+        up_with(true, None)
+    }
+
+    /// Lets GetAdaptersAddresses() do the filtering by address family.
+    pub(crate) fn up_with(all: bool, family: Option<Family>) -> io::Result<Up> {
+        up_with_scratch(all, family, Scratch::default())
+    }
+
+    /// Like up_with() but reads the adapters into the buffer in |scratch|
+    /// and refills the interfaces in it.
+    pub(crate) fn up_with_scratch(
+        all: bool,
+        family: Option<Family>,
+        mut scratch: Scratch,
+    ) -> io::Result<Up> {
+        let mut flags = GAA_FLAG_SKIP_ANYCAST
+            + GAA_FLAG_SKIP_DNS_SERVER
+            + GAA_FLAG_SKIP_MULTICAST;
+
+        if all {
+            flags += GAA_FLAG_INCLUDE_ALL_INTERFACES;
+        }
+
+        let buf = mem::take(&mut scratch.buf);
+        scratch.buf = adapters_into(family, flags, buf)?;
+
+        let buf = scratch.buf.as_mut_ptr();
+        let adapter = NonNull::new(buf as *mut IP_ADAPTER_ADDRESSES);
+
+        let address = adapter.and_then(|adapter| {
+            let adapter = unsafe { adapter.as_ref() };
+            NonNull::new(adapter.FirstUnicastAddress)
+        });
+
+        let iter = Iter { adapter, address };
+
+        Ok(Up { scratch, iter, all })
+    }
+
+    /// Returns the list of adapters that GetAdaptersAddresses() produces.
+    fn adapters(family: Option<Family>, flags: u32) -> io::Result<Vec<usize>> {
+        adapters_into(family, flags, Vec::new())
+    }
+
+    /// Like adapters() but reads into |buf|, growing it when too small.
+    /// Words rather than bytes for proper alignment.
+    fn adapters_into(
+        family: Option<Family>,
+        flags: u32,
+        mut buf: Vec<usize>,
+    ) -> io::Result<Vec<usize>> {
+        let family = match family {
+            None => PF_UNSPEC,
+            Some(Family::V4) => PF_INET,
+            Some(Family::V6) => PF_INET6,
+        };
+
+        let size = mem::size_of::<usize>();
+
+        loop {
+            let mut len = (buf.len() * size) as u32;
+
+            let result = unsafe {
+                GetAdaptersAddresses(
+                    family as _,
+                    flags,
+                    null_mut(),
+                    buf.as_mut_ptr() as *mut _,
+                    &mut len,
+                )
+            };
+
+            // Updates |len| with the actual size. Loops because adapters
+            // can come and go in between calls.
+            match result {
+                0 => return Ok(buf),
+                ERROR_BUFFER_OVERFLOW => {
+                    buf.resize((len as usize).div_ceil(size), 0)
+                }
+                _ => return Err(io::Error::from_raw_os_error(result as _)),
+            }
+        }
+    }
+
+    pub(crate) fn anycast() -> io::Result<Vec<Interface>> {
+        let flags = GAA_FLAG_SKIP_DNS_SERVER + GAA_FLAG_SKIP_MULTICAST;
+        let mut buf = adapters(None, flags)?;
+
+        let mut ifas = Vec::new();
+        let mut adapter =
+            NonNull::new(buf.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES);
+
+        while let Some(curr) = adapter {
+            let curr = unsafe { curr.as_ref() };
+            adapter = NonNull::new(curr.Next);
+
+            let mut addr = NonNull::new(curr.FirstAnycastAddress);
+
+            while let Some(anycast) = addr {
+                let anycast = unsafe { anycast.as_ref() };
+                addr = NonNull::new(anycast.Next);
+
+                let Some(address) =
+                    NonNull::new(anycast.Address.lpSockaddr).and_then(ip)
+                else {
+                    continue;
+                };
+
+                let (index, netmask) = match address {
+                    IpAddr::V4(_) => (
+                        unsafe { curr.u.s().IfIndex },
+                        IpAddr::V4(Ipv4Addr::BROADCAST),
+                    ),
+                    IpAddr::V6(_) => {
+                        (curr.Ipv6IfIndex, IpAddr::V6(Ipv6Addr::from(!0u128)))
+                    }
+                };
+
+                let mut ifa =
+                    Interface::new(friendly_name(curr), address, netmask)
+                        .with_index(index);
+                ifa.anycast = true;
+                ifas.push(ifa);
+            }
+        }
+
+        Ok(ifas)
+    }
+
+    fn friendly_name(adapter: &IP_ADAPTER_ADDRESSES) -> String {
+        let mut name = String::new();
+        push_friendly_name(&mut name, adapter);
+        name
+    }
+
+    /// Like friendly_name() but appends to |name|.
+    fn push_friendly_name(name: &mut String, adapter: &IP_ADAPTER_ADDRESSES) {
+        let wide =
+            unsafe { std::slice::from_raw_parts(adapter.FriendlyName, 256) };
+        let len = wide.iter().position(|&b| b == 0).unwrap_or(wide.len());
+        let chars = char::decode_utf16(wide[..len].iter().copied());
+        name.extend(chars.map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)));
+    }
+
+    /// Returns the index and name of every interface, including interfaces
+    /// that are down, without looking up their addresses. Cheaper than
+    /// [`all()`] when only the names are needed.
+    pub fn names() -> io::Result<Vec<(u32, String)>> {
+        let flags = GAA_FLAG_INCLUDE_ALL_INTERFACES
+            + GAA_FLAG_SKIP_ANYCAST
+            + GAA_FLAG_SKIP_DNS_SERVER
+            + GAA_FLAG_SKIP_MULTICAST
+            + GAA_FLAG_SKIP_UNICAST;
+
+        let mut buf = adapters(None, flags)?;
+
+        let mut names = Vec::new();
+        let mut adapter =
+            NonNull::new(buf.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES);
+
+        while let Some(curr) = adapter {
+            let curr = unsafe { curr.as_ref() };
+            adapter = NonNull::new(curr.Next);
+
+            let index = match unsafe { curr.u.s().IfIndex } {
+                0 => curr.Ipv6IfIndex,
+                index => index,
+            };
+
+            names.push((index, friendly_name(curr)));
+        }
+
+        Ok(names)
+    }
+
+    /// Returns the addresses of interface |name|, like [`up()`] does.
+    /// Skips the other adapters before looking at their addresses.
+    pub fn by_name(name: &str) -> io::Result<Vec<Interface>> {
+        let mut up = up()?;
+
+        let ifas = up
+            .iter
+            .by_ref()
+            .filter(|(adapter, _)| {
+                name == friendly_name(unsafe { adapter.as_ref() })
+            })
+            .filter_map(|curr| to_interface(curr, false, &mut Vec::new()).ok())
+            .collect();
+
+        Ok(ifas)
+    }
+
+    /// Like [`up()`] but also reports the entries that were skipped and why.
+    /// Useful for figuring out why an interface doesn't show up.
+    pub fn up_verbose() -> io::Result<Vec<Result<Interface, SkipReason>>> {
+        let mut up = up()?;
+        let iter = up.iter.by_ref();
+        let spare = &mut Vec::new();
+        Ok(iter.map(|curr| to_interface(curr, false, spare)).collect())
+    }
+
+    pub(crate) fn kind(hw_type: u16) -> InterfaceKind {
+        match hw_type {
+            6 => InterfaceKind::Ethernet, // IF_TYPE_ETHERNET_CSMACD
+            24 => InterfaceKind::Loopback, // IF_TYPE_SOFTWARE_LOOPBACK
+            71 => InterfaceKind::Wifi,    // IF_TYPE_IEEE80211
+            131 => InterfaceKind::Tunnel, // IF_TYPE_TUNNEL
+            135 => InterfaceKind::Vlan,   // IF_TYPE_L2_VLAN
+            209 => InterfaceKind::Bridge, // IF_TYPE_BRIDGE
+            243 | 244 => InterfaceKind::Cellular, // IF_TYPE_WWANPP(2)
+            _ => InterfaceKind::Other,
+        }
+    }
+
+    // From ws2ipdef.h, Windows' counterparts of libc::IFF_*.
+    pub(crate) const IFF_UP: u64 = 0x1;
+    pub(crate) const IFF_BROADCAST: u64 = 0x2;
+    pub(crate) const IFF_LOOPBACK: u64 = 0x4;
+    pub(crate) const IFF_POINTTOPOINT: u64 = 0x8;
+    pub(crate) const IFF_MULTICAST: u64 = 0x10;
+
+    /// Interfaces from Interface::new() or from old dumps don't have raw
+    /// flags. Derives them from the other fields instead.
+    pub(crate) fn flags(ifa: &Interface) -> InterfaceFlags {
+        if ifa.flags != 0 {
+            return InterfaceFlags::from_raw(ifa.flags);
+        }
+
+        let mut flags = InterfaceFlags::empty();
+
+        if ifa.is_up() {
+            flags |= InterfaceFlags::UP | InterfaceFlags::RUNNING;
+        }
+
+        if ifa.hw_type == 24 {
+            flags |= InterfaceFlags::LOOPBACK;
+        }
+
+        if ifa.multicast {
+            flags |= InterfaceFlags::MULTICAST;
+        }
+
+        if ifa.is_broadcast() {
+            flags |= InterfaceFlags::BROADCAST;
+        }
+
+        // PPP (23) and tunnel (131) adapters.
+        if matches!(ifa.hw_type, 23 | 131) {
+            flags |= InterfaceFlags::POINTOPOINT;
+        }
+
+        flags
+    }
+
+    /// Synthesizes the IFF_* flags that GetAdaptersAddresses() doesn't
+    /// report. |index| is the interface index for |address|.
+    fn raw_flags(
+        adapter: &IP_ADAPTER_ADDRESSES,
+        address: &IpAddr,
+        index: u32,
+    ) -> u64 {
+        let mut flags = 0;
+
+        if adapter.OperStatus == IfOperStatusUp {
+            flags |= IFF_UP;
+        }
+
+        flags |= type_flags(adapter.IfType);
+
+        if 0 == unsafe { *adapter.u1.Flags() } & IP_ADAPTER_NO_MULTICAST {
+            flags |= IFF_MULTICAST;
+        }
+
+        // Links that resolve neighbors with ARP can broadcast. IPv6 can't.
+        if address.is_ipv4()
+            && ip_interface(PF_INET as _, index)
+                .is_some_and(|row| row.SupportsNeighborDiscovery != 0)
+        {
+            flags |= IFF_BROADCAST;
+        }
+
+        flags
+    }
+
+    /// IFF_LOOPBACK or IFF_POINTTOPOINT for IF_TYPE_* |if_type|.
+    fn type_flags(if_type: u32) -> u64 {
+        match if_type {
+            24 => IFF_LOOPBACK,           // IF_TYPE_SOFTWARE_LOOPBACK
+            23 | 131 => IFF_POINTTOPOINT, // IF_TYPE_PPP, IF_TYPE_TUNNEL
+            _ => 0,
+        }
+    }
+
+    /// Looks up the primary IPv4 address of interface |name| with
+    /// GetIfEntry2(), GetIpInterfaceEntry() and GetUnicastIpAddressTable()
+    /// instead of listing every adapter with GetAdaptersAddresses().
+    pub(crate) fn query(name: &str) -> io::Result<Option<Interface>> {
+        if name.is_empty() || name.contains('\0') {
+            let msg = "invalid interface name";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        let alias: Vec<u16> = name.encode_utf16().chain([0]).collect();
+        let mut luid: NET_LUID = unsafe { mem::zeroed() };
+
+        let result =
+            unsafe { ConvertInterfaceAliasToLuid(alias.as_ptr(), &mut luid) };
+
+        if result != NO_ERROR {
+            return Err(io::Error::from_raw_os_error(result as _));
+        }
+
+        let mut row: MIB_IF_ROW2 = unsafe { mem::zeroed() };
+        row.InterfaceLuid = luid;
+
+        let result = unsafe { GetIfEntry2(&mut row) };
+
+        if result != NO_ERROR {
+            return Err(io::Error::from_raw_os_error(result as _));
+        }
+
+        // Like up(), leave out interfaces that aren't operational.
+        if row.OperStatus != IfOperStatusUp {
+            return Ok(None);
+        }
+
+        let index = row.InterfaceIndex;
+
+        // Fails when IPv4 is disabled on the interface.
+        let Some(ip_row) = ip_interface(PF_INET as _, index) else {
+            return Ok(None);
+        };
+
+        let Some((address, prefixlen)) = ipv4_address(index)? else {
+            return Ok(None);
+        };
+
+        let ones = !0u32;
+        let mask = ones & !ones.checked_shr(prefixlen.into()).unwrap_or(0);
+        let netmask = IpAddr::V4(Ipv4Addr::from(mask));
+
+        let mut ifa = Interface::new(name, address, netmask).with_index(index);
+        let len = row.PhysicalAddressLength as usize;
+        ifa.link_addr = row.PhysicalAddress[..len.min(8)].to_vec();
+        ifa.mac = ifa.link_addr[..].try_into().unwrap_or_default();
+        ifa.hw_type = row.Type as u16;
+        ifa.metric = Some(ip_row.Metric);
+        ifa.mtu = Some(ip_row.NlMtu);
+        ifa.oper_status = OperStatus::Up;
+        ifa.flags = IFF_UP | type_flags(row.Type);
+
+        if ip_row.SupportsNeighborDiscovery != 0 {
+            ifa.flags |= IFF_BROADCAST;
+        }
+
+        Ok(Some(ifa))
+    }
+
+    /// The first preferred IPv4 address of interface |index| and its prefix
+    /// length.
+    fn ipv4_address(index: u32) -> io::Result<Option<(IpAddr, u8)>> {
+        let mut table = null_mut();
+
+        let result =
+            unsafe { GetUnicastIpAddressTable(PF_INET as _, &mut table) };
+
+        if result != NO_ERROR {
+            return Err(io::Error::from_raw_os_error(result as _));
+        }
+
+        let rows = unsafe {
+            let len = (*table).NumEntries as usize;
+            std::slice::from_raw_parts((*table).Table.as_ptr(), len)
+        };
+
+        let found = rows
+            .iter()
+            .filter(|row| row.InterfaceIndex == index)
+            .filter(|row| row.DadState == IpDadStatePreferred)
+            .find_map(|row| {
+                let addr = &row.Address as *const _ as *mut SOCKADDR;
+                let address = NonNull::new(addr).and_then(ip)?;
+                Some((address, row.OnLinkPrefixLength))
+            });
+
+        unsafe { FreeMibTable(table as _) };
+
+        Ok(found)
+    }
+
+    /// The IPv4 or IPv6 properties of interface |index|.
+    fn ip_interface(family: u16, index: u32) -> Option<MIB_IPINTERFACE_ROW> {
+        let mut row: MIB_IPINTERFACE_ROW = unsafe { mem::zeroed() };
+        unsafe { InitializeIpInterfaceEntry(&mut row) };
+        row.Family = family;
+        row.InterfaceIndex = index;
+
+        let result = unsafe { GetIpInterfaceEntry(&mut row) };
+        (result == NO_ERROR).then_some(row)
+    }
+
+    /// True if interface |index| is operational, None if it doesn't exist.
+    pub(crate) fn is_running(index: u32) -> Option<bool> {
+        let mut row: MIB_IF_ROW2 = unsafe { mem::zeroed() };
+        row.InterfaceIndex = index;
+
+        if 0 != unsafe { GetIfEntry2(&mut row) } {
+            return None;
+        }
+
+        Some(row.OperStatus == IfOperStatusUp)
+    }
+
+    /// A change that Windows announced to a [`Notifier`].
+    #[derive(Debug)]
+    pub(crate) enum Notification {
+        /// Something about interface |index| changed, e.g., it connected.
+        Interface(u32),
+        NewAddr(u32, IpAddr),
+        DelAddr(u32, IpAddr),
+    }
+
+    /// Receives the notifications of NotifyIpInterfaceChange() and
+    /// NotifyUnicastIpAddressChange(). Windows calls the callbacks on
+    /// a thread pool thread; they queue the notification and wake up
+    /// [`Notifier::recv()`].
+    #[derive(Debug)]
+    pub(crate) struct Notifier {
+        // Boxed because the callbacks hold a pointer to it.
+        shared: Box<Shared>,
+        handles: [HANDLE; 2],
+    }
+
+    // The handles aren't tied to the thread that created them.
+    unsafe impl Send for Notifier {}
+
+    #[derive(Debug)]
+    struct Shared {
+        event: HANDLE,
+        notifications: Mutex<Vec<Notification>>,
+    }
+
+    impl Shared {
+        fn push(&self, notification: Notification) {
+            self.notifications
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(notification);
+
+            unsafe { SetEvent(self.event) };
+        }
+    }
+
+    impl Notifier {
+        pub(crate) fn new() -> io::Result<Self> {
+            // Manual-reset, so that applications can wait for it too, see
+            // Notifier::event().
+            let event =
+                unsafe { CreateEventW(null_mut(), TRUE, FALSE, null()) };
+
+            if event.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+
+            let notifications = Mutex::new(Vec::new());
+            let shared = Box::new(Shared {
+                event,
+                notifications,
+            });
+
+            // Drop cleans up when registering the second callback fails.
+            let mut notifier = Notifier {
+                shared,
+                handles: [null_mut(); 2],
+            };
+
+            let context = &*notifier.shared as *const Shared as *mut _;
+
+            let rc = unsafe {
+                NotifyIpInterfaceChange(
+                    PF_UNSPEC as _,
+                    Some(on_interface_change),
+                    context,
+                    0, // No initial notification.
+                    &mut notifier.handles[0],
+                )
+            };
+
+            if rc != NO_ERROR {
+                return Err(io::Error::from_raw_os_error(rc as i32));
+            }
+
+            let rc = unsafe {
+                NotifyUnicastIpAddressChange(
+                    PF_UNSPEC as _,
+                    Some(on_address_change),
+                    context,
+                    0, // No initial notification.
+                    &mut notifier.handles[1],
+                )
+            };
+
+            if rc != NO_ERROR {
+                return Err(io::Error::from_raw_os_error(rc as i32));
+            }
+
+            Ok(notifier)
+        }
+
+        /// Waits at most |timeout|, or forever when None, for
+        /// notifications. Returns Ok(None) when the timeout expires.
+        pub(crate) fn recv(
+            &self,
+            timeout: Option<Duration>,
+        ) -> io::Result<Option<Vec<Notification>>> {
+            // Rounds up, or the wait returns early and the caller spins.
+            let timeout = timeout.map_or(INFINITE, |timeout| {
+                let millis = timeout.as_nanos().div_ceil(1_000_000);
+                millis.min(INFINITE as u128 - 1) as u32
+            });
+
+            match unsafe { WaitForSingleObject(self.shared.event, timeout) } {
+                WAIT_OBJECT_0 => {}
+                WAIT_TIMEOUT => return Ok(None),
+                _ => return Err(io::Error::last_os_error()),
+            }
+
+            // Before taking the queue, or a notification that comes in
+            // between is left queued with the event reset.
+            unsafe { ResetEvent(self.shared.event) };
+
+            let mut notifications = self
+                .shared
+                .notifications
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            Ok(Some(mem::take(&mut *notifications)))
+        }
+
+        /// Event that is signaled when notifications are queued.
+        pub(crate) fn event(&self) -> HANDLE {
+            self.shared.event
+        }
+    }
+
+    impl Drop for Notifier {
+        fn drop(&mut self) {
+            // Waits for callbacks that are running to return.
+            for handle in self.handles {
+                if !handle.is_null() {
+                    unsafe { CancelMibChangeNotify2(handle) };
+                }
+            }
+
+            unsafe { CloseHandle(self.shared.event) };
+        }
+    }
+
+    unsafe extern "system" fn on_interface_change(
+        context: *mut winapi::ctypes::c_void,
+        row: *mut MIB_IPINTERFACE_ROW,
+        _: MIB_NOTIFICATION_TYPE,
+    ) {
+        let shared = &*(context as *const Shared);
+
+        if let Some(row) = row.as_ref() {
+            shared.push(Notification::Interface(row.InterfaceIndex));
+        }
+    }
+
+    unsafe extern "system" fn on_address_change(
+        context: *mut winapi::ctypes::c_void,
+        row: *mut MIB_UNICASTIPADDRESS_ROW,
+        ty: MIB_NOTIFICATION_TYPE,
+    ) {
+        let shared = &*(context as *const Shared);
+
+        let Some(row) = row.as_ref() else {
+            return;
+        };
+
+        let addr = &row.Address as *const _ as *mut SOCKADDR;
+        let Some(address) = NonNull::new(addr).and_then(ip) else {
+            return;
+        };
+
+        let index = row.InterfaceIndex;
+
+        // Ignores parameter changes, e.g., DAD finishing.
+        match ty {
+            MibAddInstance => {
+                shared.push(Notification::NewAddr(index, address))
+            }
+            MibDeleteInstance => {
+                shared.push(Notification::DelAddr(index, address))
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn byte_count(index: u32) -> Option<u64> {
+        let mut row: MIB_IF_ROW2 = unsafe { mem::zeroed() };
+        row.InterfaceIndex = index;
+
+        if 0 != unsafe { GetIfEntry2(&mut row) } {
+            return None;
+        }
+
+        Some(row.InOctets + row.OutOctets)
+    }
+
+    #[cfg(feature = "wifi")]
+    pub(crate) fn signal_strength(index: u32) -> io::Result<Option<i32>> {
+        use winapi::shared::guiddef::GUID;
+        use winapi::shared::ifdef::NET_LUID;
+        use winapi::shared::netioapi::ConvertInterfaceIndexToLuid;
+        use winapi::shared::netioapi::ConvertInterfaceLuidToGuid;
+        use winapi::shared::winerror::ERROR_INVALID_STATE;
+        use winapi::shared::winerror::ERROR_NOT_FOUND;
+        use winapi::shared::winerror::ERROR_SERVICE_NOT_ACTIVE;
+        use winapi::um::wlanapi::wlan_intf_opcode_rssi;
+        use winapi::um::wlanapi::WlanCloseHandle;
+        use winapi::um::wlanapi::WlanFreeMemory;
+        use winapi::um::wlanapi::WlanOpenHandle;
+        use winapi::um::wlanapi::WlanQueryInterface;
+
+        let mut luid: NET_LUID = unsafe { mem::zeroed() };
+        let mut guid: GUID = unsafe { mem::zeroed() };
+
+        let rc = unsafe { ConvertInterfaceIndexToLuid(index, &mut luid) };
+
+        if rc != 0 {
+            return Err(io::Error::from_raw_os_error(rc as _));
+        }
+
+        let rc = unsafe { ConvertInterfaceLuidToGuid(&luid, &mut guid) };
+
+        if rc != 0 {
+            return Err(io::Error::from_raw_os_error(rc as _));
+        }
+
+        let mut version = 0;
+        let mut handle = null_mut();
+
+        let rc =
+            unsafe { WlanOpenHandle(2, null_mut(), &mut version, &mut handle) };
+
+        // WLAN AutoConfig service not running, i.e., no Wi-Fi.
+        if rc == ERROR_SERVICE_NOT_ACTIVE {
+            return Ok(None);
+        }
+
+        if rc != 0 {
+            return Err(io::Error::from_raw_os_error(rc as _));
+        }
+
+        let mut len = 0;
+        let mut data = null_mut();
+
+        let rc = unsafe {
+            WlanQueryInterface(
+                handle,
+                &guid,
+                wlan_intf_opcode_rssi,
+                null_mut(),
+                &mut len,
+                &mut data,
+                null_mut(),
+            )
+        };
+
+        let result = match rc {
+            0 => {
+                let rssi = unsafe { *(data as *const i32) };
+                unsafe { WlanFreeMemory(data) };
+                Ok(Some(rssi))
+            }
+            // Not a Wi-Fi interface or not associated.
+            ERROR_NOT_FOUND | ERROR_INVALID_STATE => Ok(None),
+            rc => Err(io::Error::from_raw_os_error(rc as _)),
+        };
+
+        unsafe { WlanCloseHandle(handle, null_mut()) };
+
+        result
+    }
+
+    pub struct Up {
+        scratch: Scratch, // Owns the adapters that |iter| points into.
+        iter: Iter,
+        all: bool,
+    }
+
+    impl Up {
+        /// Hands |ifas| to next() to refill, see Snapshot::refresh().
+        pub(crate) fn recycle(&mut self, ifas: &mut Vec<Interface>) {
+            self.scratch.spare.append(ifas);
+        }
+
+        pub(crate) fn into_scratch(mut self) -> Scratch {
+            mem::take(&mut self.scratch)
+        }
+    }
+
+    impl Iterator for Up {
+        type Item = Interface;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let (all, spare) = (self.all, &mut self.scratch.spare);
+            self.iter
+                .find_map(|curr| to_interface(curr, all, spare).ok())
+        }
+
+        /// Walks the rest of the adapter list, it's already in memory.
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (0, Some(self.iter.clone().count()))
+        }
+
+        /// Skips to_interface() and with it the friendly name and other
+        /// conversions that count() doesn't need.
+        fn count(mut self) -> usize {
+            let all = self.all;
+            self.iter
+                .by_ref()
+                .filter(|&curr| check(curr, all).is_ok())
+                .count()
+        }
+    }
+
+    impl Drop for Up {
+        fn drop(&mut self) {}
+    }
+
+    #[derive(Clone)]
+    struct Iter {
+        adapter: Option<NonNull<IP_ADAPTER_ADDRESSES>>,
+        address: Option<NonNull<IP_ADAPTER_UNICAST_ADDRESS>>,
+    }
+
+    impl Iterator for Iter {
+        type Item = (
+            NonNull<IP_ADAPTER_ADDRESSES>,
+            NonNull<IP_ADAPTER_UNICAST_ADDRESS>,
+        );
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let adapter = self.adapter?;
+
+                if let Some(address) = self.address.take() {
+                    self.address =
+                        NonNull::new(unsafe { address.as_ref().Next });
+                    return Some((adapter, address));
+                }
+
+                self.adapter = NonNull::new(unsafe { adapter.as_ref().Next });
+
+                self.address = self.adapter.and_then(|adapter| {
+                    let adapter = unsafe { adapter.as_ref() };
+                    NonNull::new(adapter.FirstUnicastAddress)
+                });
+            }
+        }
+    }
+
+    fn ip(addr: NonNull<SOCKADDR>) -> Option<IpAddr> {
+        let family = unsafe { addr.as_ref().sa_family };
+
+        match family as _ {
+            PF_INET => {
+                let addr = addr.as_ptr() as *mut SOCKADDR_IN;
+                let addr = unsafe { *(*addr).sin_addr.S_un.S_addr() };
+                let addr = Ipv4Addr::from(u32::from_be(addr));
+                Some(IpAddr::V4(addr))
+            }
+            PF_INET6 => {
+                let addr = addr.as_ptr() as *mut SOCKADDR_IN6;
+                let [b0, b1, b2, b3, b4, b5, b6, b7, b8, b9, b10, b11, b12, b13, b14, b15] =
+                    *unsafe { (*addr).sin6_addr.u.Byte() };
+                let s0 = 256 * b0 as u16 + b1 as u16;
+                let s1 = 256 * b2 as u16 + b3 as u16;
+                let s2 = 256 * b4 as u16 + b5 as u16;
+                let s3 = 256 * b6 as u16 + b7 as u16;
+                let s4 = 256 * b8 as u16 + b9 as u16;
+                let s5 = 256 * b10 as u16 + b11 as u16;
+                let s6 = 256 * b12 as u16 + b13 as u16;
+                let s7 = 256 * b14 as u16 + b15 as u16;
+                let addr = Ipv6Addr::new(s0, s1, s2, s3, s4, s5, s6, s7);
+                Some(IpAddr::V6(addr))
+            }
+            _ => None,
+        }
+    }
+
+    /// The cheap part of to_interface(): decides whether the address is
+    /// skipped without converting the rest of the adapter.
+    fn check(
+        (adapter, addr): (
+            NonNull<IP_ADAPTER_ADDRESSES>,
+            NonNull<IP_ADAPTER_UNICAST_ADDRESS>,
+        ),
+        all: bool,
+    ) -> Result<IpAddr, SkipReason> {
+        let adapter = unsafe { adapter.as_ref() };
+
+        if !all && adapter.OperStatus != IfOperStatusUp {
+            return Err(SkipReason::NotUp(friendly_name(adapter)));
+        }
+
+        let addr = unsafe { addr.as_ref() };
+        let Some(sockaddr) = NonNull::new(addr.Address.lpSockaddr) else {
+            return Err(SkipReason::NoAddress(friendly_name(adapter)));
+        };
+
+        let Some(address) = ip(sockaddr) else {
+            let name = friendly_name(adapter);
+            let family = unsafe { sockaddr.as_ref().sa_family };
+            return Err(SkipReason::UnsupportedFamily(name, family as _));
+        };
+
+        Ok(address)
+    }
+
+    fn to_interface(
+        (adapter, addr): (
+            NonNull<IP_ADAPTER_ADDRESSES>,
+            NonNull<IP_ADAPTER_UNICAST_ADDRESS>,
+        ),
+        all: bool,
+        spare: &mut Vec<Interface>,
+    ) -> Result<Interface, SkipReason> {
+        let address = check((adapter, addr), all)?;
+        let adapter = unsafe { adapter.as_ref() };
+        let (mut name, mut link_addr) = buffers(spare);
+        push_friendly_name(&mut name, adapter);
+
+        let dormant = adapter.OperStatus == IfOperStatusDormant;
+        let oper_status = OperStatus::from_rfc2863(adapter.OperStatus as u32);
+
+        let addr = unsafe { addr.as_ref() };
+        let prefixlen = addr.OnLinkPrefixLength as _;
+
+        let netmask = match address {
+            IpAddr::V4(_) => {
+                let ones = !0u32;
+                let mask = ones & !ones.checked_shr(prefixlen).unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(mask))
+            }
+            IpAddr::V6(_) => {
+                let ones = !0u128;
+                let mask = ones & !ones.checked_shr(prefixlen).unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(mask))
+            }
+        };
+
+        let scope_id = address.is_ipv6().then(|| {
+            let addr = addr.Address.lpSockaddr as *const SOCKADDR_IN6;
+            unsafe { *(*addr).u.sin6_scope_id() }
+        });
+
+        let [b0, b1, b2, b3, b4, b5, _, _] = adapter.PhysicalAddress;
+        let mac = [b0, b1, b2, b3, b4, b5];
+        let len = adapter.PhysicalAddressLength as usize;
+        link_addr.extend_from_slice(&adapter.PhysicalAddress[..len.min(8)]);
+        let hw_type = adapter.IfType as u16;
+
+        let index = match address {
+            IpAddr::V6(_) if adapter.Ipv6IfIndex != 0 => adapter.Ipv6IfIndex,
+            _ => unsafe { adapter.u.s().IfIndex },
+        };
+
+        let flags = raw_flags(adapter, &address, index);
+        let secondary = false;
+
+        let metric = match address {
+            IpAddr::V4(_) => adapter.Ipv4Metric,
+            IpAddr::V6(_) => adapter.Ipv6Metric,
+        };
+        let metric = Some(metric);
+
+        let adapter_flags = unsafe { *adapter.u1.Flags() };
+        let multicast = 0 == adapter_flags & IP_ADAPTER_NO_MULTICAST;
+        let anycast = false;
+
+        let dhcp = addr.SuffixOrigin == IpSuffixOriginDhcp;
+        let lease_expiry =
+            (dhcp && addr.LeaseLifetime != INFINITE).then(|| {
+                let lifetime = Duration::from_secs(addr.LeaseLifetime.into());
+                SystemTime::now() + lifetime
+            });
+
+        let tentative = addr.DadState != IpDadStatePreferred
+            && addr.DadState != IpDadStateDeprecated;
+        let deprecated = addr.DadState == IpDadStateDeprecated;
+        let destination = None;
+        let broadcast = crate::directed_broadcast(address, netmask);
+
+        let speed = adapter.TransmitLinkSpeed;
+        let speed = (speed != 0 && speed != !0).then_some(speed);
+
+        let mtu = adapter.Mtu;
+        let mtu = (mtu != 0 && mtu != !0).then_some(mtu);
+        let kernel_scope = None;
+
+        Ok(Interface {
+            name,
+            index,
+            flags,
+            mac,
+            link_addr,
+            hw_type,
+            address,
+            scope_id,
+            netmask,
+            secondary,
+            metric,
+            multicast,
+            lease_expiry,
+            tentative,
+            deprecated,
+            destination,
+            broadcast,
+            speed,
+            dormant,
+            anycast,
+            mtu,
+            kernel_scope,
+            oper_status,
+        })
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod unix {
+    use super::Family;
+    use super::Interface;
+    use super::OperStatus;
+    use super::SkipReason;
+    use crate::snapshot::buffers;
+    use crate::snapshot::Scratch;
+    use libc as c;
+    use std::cell::OnceCell;
+    use std::ffi::CStr;
+    use std::io;
+    use std::iter;
+    use std::mem;
+    use std::net::IpAddr;
+    use std::net::Ipv4Addr;
+    use std::net::Ipv6Addr;
+    use std::ptr;
+    use std::ptr::NonNull;
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    use crate::linux::*;
+
+    // Yes, wrong for Solaris's vile offspring. Don't complain, send patches.
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    use crate::bsd::*;
+
+    /// Returns an iterator that produces the addresses of the interfaces
+    /// that getifaddrs(3) reports. Doesn't filter on link state: the
+    /// addresses of interfaces that are administratively down are
+    /// included, see [`Interface::flags()`] and
+    /// [`Interface::oper_status()`].
+    pub fn up() -> io::Result<Up> {
+        up_with(false, None)
+    }
+
+    /// Same as [`up()`]. On Windows, up() leaves out adapters that aren't
+    /// operational and all() includes them. On Unix, up() doesn't filter
+    /// on link state to begin with.
+    pub fn all() -> io::Result<Up> {
+        up_with(true, None)
+    }
+
+    /// Entries of the wrong address family are skipped before they are
+    /// converted, saving the link-layer lookup and friends. |all| makes
+    /// no difference, see [`all()`].
+    pub(crate) fn up_with(all: bool, family: Option<Family>) -> io::Result<Up> {
+        up_with_scratch(all, family, Scratch::default())
+    }
+
+    /// Like up_with() but refills the interfaces in |scratch|.
+    pub(crate) fn up_with_scratch(
+        _all: bool,
+        family: Option<Family>,
+        scratch: Scratch,
+    ) -> io::Result<Up> {
+        let mut base = ptr::null_mut();
+
+        if 0 != unsafe { c::getifaddrs(&mut base) } {
+            return Err(io::Error::last_os_error());
+        }
+
+        let list = Iter::new(NonNull::new(base));
+        let iter = list;
+        let table = OnceCell::new();
+        let owned = true;
+
+        Ok(Up {
+            list,
+            iter,
+            table,
+            owned,
+            family,
+            scratch,
+        })
+    }
+
+    /// Returns an iterator over the interfaces in |base|, a list that the
+    /// caller obtained from getifaddrs(3) or constructed by hand. Unlike
+    /// [`up()`], the iterator does not free the list, and it doesn't ask
+    /// the operating system for what the list doesn't say, like the MTU,
+    /// link speed or DAD state. Those are zero, false or None.
+    ///
+    /// # Safety
+    ///
+    /// |base| must be null or point to a well-formed ifaddrs list that
+    /// stays valid and unmodified for as long as the iterator is alive.
+    pub unsafe fn from_ifaddrs(
+        base: *mut c::ifaddrs,
+    ) -> impl Iterator<Item = Interface> {
+        let list = Iter::new(NonNull::new(base));
+        let iter = list;
+        let table = OnceCell::new();
+        let owned = false;
+        let family = None;
+        let scratch = Scratch::default();
+
+        Up {
+            list,
+            iter,
+            table,
+            owned,
+            family,
+            scratch,
+        }
+    }
+
+    pub struct Up {
+        /// The whole list, for looking up link-layer entries. Walking it
+        /// again doesn't repeat the cycle check.
+        list: Iter,
+        iter: Iter,
+        table: OnceCell<AddrTable>,
+        owned: bool,
+        family: Option<Family>,
+        scratch: Scratch,
+    }
+
+    impl Up {
+        /// Hands |ifas| to next() to refill, see Snapshot::refresh().
+        pub(crate) fn recycle(&mut self, ifas: &mut Vec<Interface>) {
+            self.scratch.spare.append(ifas);
+        }
+
+        pub(crate) fn into_scratch(mut self) -> Scratch {
+            mem::take(&mut self.scratch)
+        }
+    }
+
+    impl Iterator for Up {
+        type Item = Interface;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let (list, table, live) = (self.list, &self.table, self.owned);
+            let (family, spare) = (self.family, &mut self.scratch.spare);
+
+            self.iter
+                .by_ref()
+                .filter(|&curr| match family {
+                    Some(family) => has_family(curr, family),
+                    None => true,
+                })
+                .find_map(|curr| {
+                    to_interface(list, table, live, curr, spare).ok()
+                })
+        }
 
-    /// Returns an iterator that produces the list of interfaces that the
-    /// operating system considers "up", that is, configured and active.
-    pub fn up() -> io::Result<Up> {
-        let mut len = 0;
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (0, Some(self.iter.remaining))
+        }
 
-        let flags = GAA_FLAG_SKIP_ANYCAST
-            + GAA_FLAG_SKIP_DNS_SERVER
-            + GAA_FLAG_SKIP_MULTICAST;
+        /// Skips to_interface() and with it the name, MAC address and
+        /// sysfs lookups that count() doesn't need.
+        fn count(mut self) -> usize {
+            let family = self.family;
 
-        // Fails with ERROR_BUFFER_OVERFLOW but updates |len| with actual size.
-        unsafe {
-            GetAdaptersAddresses(
-                PF_UNSPEC as _,
-                flags,
-                null_mut(),
-                null_mut(),
-                &mut len,
-            );
+            self.iter
+                .by_ref()
+                .filter(|&curr| match family {
+                    Some(family) => has_family(curr, family),
+                    None => true,
+                })
+                .filter(|&curr| check(curr).is_ok())
+                .count()
         }
+    }
 
-        // Over-allocates 8x but easiest for proper alignment.
-        let mut buf = vec![0usize; len as _];
+    /// The cheap part of to_interface(): decides whether |curr| is skipped
+    /// without looking at the rest of the list.
+    fn check(
+        curr: NonNull<c::ifaddrs>,
+    ) -> Result<(NonNull<c::sockaddr>, IpAddr), SkipReason> {
+        let curr = unsafe { curr.as_ref() };
+        let name = unsafe { CStr::from_ptr(curr.ifa_name) };
+        let lossy = || name.to_string_lossy().into_owned();
 
-        let result = unsafe {
-            GetAdaptersAddresses(
-                PF_UNSPEC as _,
-                flags,
-                null_mut(),
-                buf.as_mut_ptr() as *mut _,
-                &mut len,
-            )
+        let Some(addr) = NonNull::new(curr.ifa_addr) else {
+            return Err(SkipReason::NoAddress(lossy()));
         };
 
-        if result != 0 {
-            return Err(io::Error::from_raw_os_error(result as _));
+        if is_link(addr) {
+            return Err(SkipReason::LinkLayer(lossy()));
         }
 
-        let adapter =
-            NonNull::new(buf.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES);
+        let Some(address) = ip(addr) else {
+            let family = unsafe { addr.as_ref().sa_family };
+            return Err(SkipReason::UnsupportedFamily(lossy(), family as _));
+        };
 
-        let address = adapter.and_then(|adapter| {
-            let adapter = unsafe { adapter.as_ref() };
-            NonNull::new(adapter.FirstUnicastAddress)
-        });
+        Ok((addr, address))
+    }
 
-        let iter = Iter { adapter, address };
+    fn has_family(curr: NonNull<c::ifaddrs>, family: Family) -> bool {
+        let Some(addr) = NonNull::new(unsafe { curr.as_ref() }.ifa_addr) else {
+            return false;
+        };
+
+        let expected = match family {
+            Family::V4 => c::AF_INET,
+            Family::V6 => c::AF_INET6,
+        };
 
-        Ok(Up { _buf: buf, iter })
+        expected == unsafe { addr.as_ref().sa_family } as _
     }
 
-    pub struct Up {
-        _buf: Vec<usize>, // Over-allocates 8x but easiest for proper alignment.
-        iter: Iter,
+    /// Like [`up()`] but also reports the entries that were skipped and why.
+    /// Useful for figuring out why an interface doesn't show up.
+    pub fn up_verbose() -> io::Result<Vec<Result<Interface, SkipReason>>> {
+        let mut up = up()?;
+        let Up {
+            list, iter, table, ..
+        } = &mut up;
+        let spare = &mut Vec::new();
+        let iter =
+            iter.map(|curr| to_interface(*list, table, true, curr, spare));
+        Ok(iter.collect())
     }
 
-    impl Iterator for Up {
-        type Item = Interface;
+    impl Drop for Up {
+        fn drop(&mut self) {
+            if let Some(mut base) = self.list.curr.filter(|_| self.owned) {
+                unsafe { c::freeifaddrs(base.as_mut()) };
+            }
+        }
+    }
 
-        fn next(&mut self) -> Option<Self::Item> {
-            self.iter.find_map(to_interface)
+    /// Returns the addresses of interface |name|, like [`up()`] does.
+    /// Skips the entries of other interfaces before converting them, which
+    /// saves looking up their link-layer details.
+    pub fn by_name(name: &str) -> io::Result<Vec<Interface>> {
+        let mut up = up()?;
+        let Up {
+            list, iter, table, ..
+        } = &mut up;
+
+        let ifas = iter
+            .filter(|curr| {
+                let ifa_name = unsafe { curr.as_ref() }.ifa_name;
+                name.as_bytes()
+                    == unsafe { CStr::from_ptr(ifa_name) }.to_bytes()
+            })
+            .filter_map(|curr| {
+                to_interface(*list, table, true, curr, &mut Vec::new()).ok()
+            })
+            .collect();
+
+        Ok(ifas)
+    }
+
+    /// Returns the index and name of every interface, including interfaces
+    /// that are down, without looking up their addresses. Cheaper than
+    /// [`all()`] when only the names are needed.
+    pub fn names() -> io::Result<Vec<(u32, String)>> {
+        let base = unsafe { c::if_nameindex() };
+
+        if base.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut names = Vec::new();
+        let mut curr = base;
+
+        // Terminated by an entry with index zero and a null name.
+        while let Some(entry) = unsafe { curr.as_ref() } {
+            if entry.if_index == 0 || entry.if_name.is_null() {
+                break;
+            }
+
+            let name = unsafe { CStr::from_ptr(entry.if_name) };
+            names.push((entry.if_index, name.to_string_lossy().into_owned()));
+            curr = unsafe { curr.add(1) };
         }
+
+        unsafe { c::if_freenameindex(base) };
+
+        Ok(names)
     }
 
-    impl Drop for Up {
-        fn drop(&mut self) {}
+    pub(crate) fn index_to_name(index: u32) -> Option<String> {
+        let mut buf = [0; c::IF_NAMESIZE];
+        let name = unsafe { c::if_indextoname(index, buf.as_mut_ptr()) };
+
+        if name.is_null() {
+            return None;
+        }
+
+        let name = unsafe { CStr::from_ptr(name) };
+        Some(name.to_string_lossy().into_owned())
+    }
+
+    pub(crate) struct Link {
+        pub(crate) index: u32,
+        pub(crate) link_addr: Vec<u8>,
+        pub(crate) hw_type: u16,
+        pub(crate) speed: Option<u64>,
+        pub(crate) mtu: Option<u32>,
     }
 
+    /// Walks an ifaddrs list. Stops after visiting every entry once, even
+    /// when the list is malformed and its ifa_next pointers form a cycle.
+    #[derive(Clone, Copy)]
     struct Iter {
-        adapter: Option<NonNull<IP_ADAPTER_ADDRESSES>>,
-        address: Option<NonNull<IP_ADAPTER_UNICAST_ADDRESS>>,
+        curr: Option<NonNull<c::ifaddrs>>,
+        remaining: usize,
+    }
+
+    impl Iter {
+        fn new(base: Option<NonNull<c::ifaddrs>>) -> Self {
+            let remaining = distinct_len(base);
+            Iter {
+                curr: base,
+                remaining,
+            }
+        }
     }
 
     impl Iterator for Iter {
-        type Item = (
-            NonNull<IP_ADAPTER_ADDRESSES>,
-            NonNull<IP_ADAPTER_UNICAST_ADDRESS>,
-        );
+        type Item = NonNull<c::ifaddrs>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            loop {
-                let adapter = self.adapter?;
+            self.remaining = self.remaining.checked_sub(1)?;
+            let curr = self.curr?;
+            mem::replace(&mut self.curr, next(curr))
+        }
+    }
 
-                if let Some(address) = self.address.take() {
-                    self.address =
-                        NonNull::new(unsafe { address.as_ref().Next });
-                    return Some((adapter, address));
-                }
+    fn next(curr: NonNull<c::ifaddrs>) -> Option<NonNull<c::ifaddrs>> {
+        NonNull::new(unsafe { curr.as_ref().ifa_next })
+    }
 
-                self.adapter = NonNull::new(unsafe { adapter.as_ref().Next });
+    /// Number of distinct entries in the list. Uses Floyd's algorithm to
+    /// find the start and length of a cycle, if there is one.
+    fn distinct_len(base: Option<NonNull<c::ifaddrs>>) -> usize {
+        let mut slow = base;
+        let mut fast = base;
 
-                self.address = self.adapter.and_then(|adapter| {
-                    let adapter = unsafe { adapter.as_ref() };
-                    NonNull::new(adapter.FirstUnicastAddress)
-                });
+        let meet = loop {
+            slow = slow.and_then(next);
+            fast = fast.and_then(next).and_then(next);
+
+            match (slow, fast) {
+                (Some(slow), Some(fast)) if slow == fast => break slow,
+                (_, Some(_)) => continue,
+                // No cycle.
+                (_, None) => {
+                    return iter::successors(base, |&p| next(p)).count()
+                }
             }
+        };
+
+        // Entries before the start of the cycle.
+        let mut tail = 0;
+        let mut p = base;
+        let mut q = Some(meet);
+
+        while p != q {
+            p = p.and_then(next);
+            q = q.and_then(next);
+            tail += 1;
         }
+
+        // Entries in the cycle.
+        let mut cycle = 1;
+        let mut r = next(meet);
+
+        while r != Some(meet) {
+            r = r.and_then(next);
+            cycle += 1;
+        }
+
+        tail + cycle
     }
 
-    fn ip(addr: NonNull<SOCKADDR>) -> Option<IpAddr> {
+    fn ip(addr: NonNull<c::sockaddr>) -> Option<IpAddr> {
         let family = unsafe { addr.as_ref().sa_family };
 
         match family as _ {
-            PF_INET => {
-                let addr = addr.as_ptr() as *mut SOCKADDR_IN;
-                let addr = unsafe { *(*addr).sin_addr.S_un.S_addr() };
-                let addr = Ipv4Addr::from(u32::from_be(addr));
+            c::AF_INET => {
+                let addr = unsafe { &*(addr.as_ptr() as *mut c::sockaddr_in) };
+                let addr = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
                 Some(IpAddr::V4(addr))
             }
-            PF_INET6 => {
-                let addr = addr.as_ptr() as *mut SOCKADDR_IN6;
+            c::AF_INET6 => {
+                let addr = unsafe { &*(addr.as_ptr() as *mut c::sockaddr_in6) };
                 let [b0, b1, b2, b3, b4, b5, b6, b7, b8, b9, b10, b11, b12, b13, b14, b15] =
-                    *unsafe { (*addr).sin6_addr.u.Byte() };
+                    addr.sin6_addr.s6_addr;
                 let s0 = 256 * b0 as u16 + b1 as u16;
                 let s1 = 256 * b2 as u16 + b3 as u16;
                 let s2 = 256 * b4 as u16 + b5 as u16;
@@ -214,211 +3033,731 @@ mod windows {
         }
     }
 
+    /// Looks up what the entry doesn't say when |live| is set, i.e., when
+    /// the list comes from getifaddrs(3). Lists from the caller are taken
+    /// as is, they'd pick up details of the host's interfaces otherwise.
     fn to_interface(
-        (adapter, addr): (
-            NonNull<IP_ADAPTER_ADDRESSES>,
-            NonNull<IP_ADAPTER_UNICAST_ADDRESS>,
-        ),
-    ) -> Option<Interface> {
-        let adapter = unsafe { adapter.as_ref() };
-
-        if adapter.OperStatus != IfOperStatusUp {
-            return None;
-        }
-
-        let addr = unsafe { addr.as_ref() };
-        let sockaddr = NonNull::new(addr.Address.lpSockaddr)?;
-        let prefixlen = addr.OnLinkPrefixLength as _;
+        mut list: Iter,
+        table: &OnceCell<AddrTable>,
+        live: bool,
+        curr: NonNull<c::ifaddrs>,
+        spare: &mut Vec<Interface>,
+    ) -> Result<Interface, SkipReason> {
+        let (addr, address) = check(curr)?;
+        let curr = unsafe { curr.as_ref() };
+        let name = unsafe { CStr::from_ptr(curr.ifa_name) };
+        let (mut name_buf, mut link_addr) = buffers(spare);
 
-        let address = ip(sockaddr)?;
+        // Some point-to-point and tunnel interfaces on the BSDs don't have
+        // a netmask. Treat them as single-address networks.
+        let netmask = NonNull::new(curr.ifa_netmask).and_then(ip).unwrap_or(
+            match address {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::BROADCAST),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(!0u128)),
+            },
+        );
 
-        let netmask = match address {
-            IpAddr::V4(_) => {
-                let ones = !0u32;
-                let mask = ones & !ones.checked_shr(prefixlen).unwrap_or(0);
-                IpAddr::V4(Ipv4Addr::from(mask))
-            }
-            IpAddr::V6(_) => {
-                let ones = !0u128;
-                let mask = ones & !ones.checked_shr(prefixlen).unwrap_or(0);
-                IpAddr::V6(Ipv6Addr::from(mask))
-            }
+        let link = list
+            .find_map(|link| link_of(name, link, live))
+            .or_else(|| link_by_name(name).filter(|_| live));
+        if let Some(link) = &link {
+            link_addr.extend_from_slice(&link.link_addr);
+        }
+        let mac = link_addr[..].try_into().unwrap_or_default();
+        let hw_type = link.as_ref().map_or(0, |link| link.hw_type);
+        let speed = link.as_ref().and_then(|link| link.speed);
+        let mtu = link.as_ref().and_then(|link| link.mtu);
+        let index = match link {
+            Some(link) => link.index,
+            None if live => unsafe { c::if_nametoindex(name.as_ptr()) },
+            None => 0,
         };
+        name_buf.push_str(&name.to_string_lossy());
+        let name = name_buf;
 
-        let name =
-            unsafe { std::slice::from_raw_parts(adapter.FriendlyName, 256) };
-        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
-        let name = String::from_utf16_lossy(&name[..len]);
+        let flags = From::from(curr.ifa_flags);
 
         let scope_id = address.is_ipv6().then(|| {
-            let addr = addr.Address.lpSockaddr as *const SOCKADDR_IN6;
-            unsafe { *(*addr).u.sin6_scope_id() }
+            let addr = addr.as_ptr() as *const c::sockaddr_in6;
+            unsafe { (*addr).sin6_scope_id }
         });
 
-        let [b0, b1, b2, b3, b4, b5, _, _] = adapter.PhysicalAddress;
-        let mac = [b0, b1, b2, b3, b4, b5];
+        // Built here rather than in up_with() so count() doesn't pay for it.
+        let table = table.get_or_init(|| match live {
+            true => AddrTable::new(),
+            false => AddrTable::empty(),
+        });
+        let secondary = table.is_secondary(index, &address);
+        let metric = None;
+        let multicast = 0 != curr.ifa_flags & c::IFF_MULTICAST as c::c_uint;
+        let dormant = is_dormant(curr);
+        let oper_status = oper_status(curr).filter(|_| live).unwrap_or({
+            let flags = curr.ifa_flags;
+            if 0 == flags & c::IFF_UP as c::c_uint {
+                OperStatus::Down
+            } else if dormant {
+                OperStatus::Dormant
+            } else if 0 != flags & c::IFF_RUNNING as c::c_uint {
+                OperStatus::Up
+            } else {
+                OperStatus::LowerLayerDown
+            }
+        });
+        let anycast = false;
+        let lease_expiry = None;
+        let tentative = table.is_tentative(index, &address);
+        let deprecated = table.is_deprecated(index, &address);
+        let kernel_scope = table.scope(index, &address);
 
-        let flags = 0;
+        // Shares storage with the broadcast address on some platforms.
+        let ptp = 0 != curr.ifa_flags & c::IFF_POINTOPOINT as c::c_uint;
+        let destination = NonNull::new(dstaddr(curr)).filter(|_| ptp);
+        let destination = destination.and_then(ip);
 
-        Some(Interface {
+        let bcast = 0 != curr.ifa_flags & c::IFF_BROADCAST as c::c_uint;
+        let broadcast = NonNull::new(dstaddr(curr)).filter(|_| bcast && !ptp);
+        let broadcast = broadcast.and_then(ip).filter(IpAddr::is_ipv4);
+
+        Ok(Interface {
             name,
+            index,
             flags,
             mac,
+            link_addr,
+            hw_type,
             address,
             scope_id,
             netmask,
+            secondary,
+            metric,
+            multicast,
+            lease_expiry,
+            tentative,
+            deprecated,
+            destination,
+            broadcast,
+            speed,
+            dormant,
+            anycast,
+            mtu,
+            kernel_scope,
+            oper_status,
         })
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-mod unix {
-    use super::Interface;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod linux {
+    use crate::netlink;
+    use crate::unix::index_to_name;
+    use crate::unix::Link;
+    use crate::AddressScope;
+    use crate::Interface;
+    use crate::InterfaceKind;
+    use crate::MacAddr;
+    use crate::OperStatus;
+    use crate::RaFlags;
     use libc as c;
     use std::ffi::CStr;
+    use std::ffi::CString;
+    use std::fs;
     use std::io;
     use std::mem;
     use std::net::IpAddr;
-    use std::net::Ipv4Addr;
     use std::net::Ipv6Addr;
-    use std::ptr;
+    use std::os::fd::AsRawFd;
+    use std::os::fd::FromRawFd;
+    use std::os::fd::OwnedFd;
+    use std::panic;
     use std::ptr::NonNull;
+    use std::thread;
 
-    #[cfg(any(target_os = "android", target_os = "linux"))]
-    use crate::linux::*;
+    const IFALIASZ: usize = 256;
 
-    // Yes, wrong for Solaris's vile offspring. Don't complain, send patches.
-    #[cfg(not(any(target_os = "android", target_os = "linux")))]
-    use crate::bsd::*;
+    const RT_TABLE_MAIN: u32 = 254;
+
+    // Not in libc.
+    const ARPHRD_RAWIP: u16 = 519;
+    const ARPHRD_IP6GRE: u16 = 823;
+
+    /// Returns the description ("alias") of interface |name|, as set with
+    /// `ip link set <name> alias <description>`, or None if not set.
+    /// Linux only.
+    pub fn alias(name: &str) -> io::Result<Option<String>> {
+        let alias = fs::read_to_string(sysfs_path(name, "ifalias")?)?;
+        let alias = alias.trim_end_matches('\n');
+        Ok((!alias.is_empty()).then(|| alias.to_owned()))
+    }
+
+    /// Sets the description ("alias") of interface |name|. An empty string
+    /// clears it. Requires CAP_NET_ADMIN. Linux only.
+    pub fn set_alias(name: &str, alias: &str) -> io::Result<()> {
+        if alias.len() >= IFALIASZ || alias.contains('\n') {
+            let msg = "alias too long or contains newlines";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        let path = sysfs_path(name, "ifalias")?;
+
+        fs::write(path, format!("{alias}\n")).map_err(|err| {
+            if err.kind() == io::ErrorKind::PermissionDenied {
+                let msg = "setting the alias requires CAP_NET_ADMIN";
+                io::Error::new(err.kind(), msg)
+            } else {
+                err
+            }
+        })
+    }
+
+    /// Returns the IPv6 prefixes that are routed out of interface |name|,
+    /// e.g., the prefixes that a router delegated with DHCPv6-PD and that
+    /// were assigned to the LAN interface. Link-local, multicast, host
+    /// and default routes are left out. Linux only.
+    pub fn delegated_prefixes(name: &str) -> io::Result<Vec<(Ipv6Addr, u8)>> {
+        let index = index_of(name)?;
+
+        let prefixes = netlink::routes6()?
+            .into_iter()
+            .filter(|route| route.index == index)
+            .filter(|route| route.table == RT_TABLE_MAIN)
+            .filter(|route| (1..128).contains(&route.dst_len))
+            .filter_map(|route| match route.dst {
+                IpAddr::V6(dst) => Some((dst, route.dst_len)),
+                IpAddr::V4(_) => None,
+            })
+            .filter(|(dst, _)| !dst.is_multicast())
+            .filter(|(dst, _)| !crate::is_link_local(&IpAddr::V6(*dst)))
+            .collect();
+
+        Ok(prefixes)
+    }
+
+    fn index_of(name: &str) -> io::Result<u32> {
+        let name = CString::new(name).map_err(|_| {
+            let msg = "invalid interface name";
+            io::Error::new(io::ErrorKind::InvalidInput, msg)
+        })?;
+
+        match unsafe { c::if_nametoindex(name.as_ptr()) } {
+            0 => Err(io::Error::last_os_error()),
+            index => Ok(index),
+        }
+    }
+
+    /// Network namespace, see [`up_all_netns()`].
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    pub enum NetnsId {
+        /// The namespace of the calling thread.
+        Current,
+        /// A namespace from /var/run/netns, as created with `ip netns add`.
+        Named(String),
+    }
+
+    /// Returns the interfaces of the current network namespace and of
+    /// the named namespaces in /var/run/netns. Entering a namespace
+    /// requires CAP_SYS_ADMIN. sysfs shows the namespace it was mounted
+    /// in, so interfaces of other namespaces take their MTU, hardware
+    /// type and operational status from netlink instead, and have no
+    /// link speed. Linux only.
+    pub fn up_all_netns() -> io::Result<Vec<(NetnsId, Interface)>> {
+        let mut ifas = crate::up()?
+            .map(|ifa| (NetnsId::Current, ifa))
+            .collect::<Vec<_>>();
+
+        let entries = match fs::read_dir("/var/run/netns") {
+            Ok(entries) => entries,
+            // No named namespaces.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(ifas)
+            }
+            Err(err) => return Err(err),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let file = fs::File::open(entry.path())?;
+
+            // setns() switches the calling thread, not the process.
+            let up = thread::spawn(move || {
+                let rc = unsafe { c::setns(file.as_raw_fd(), c::CLONE_NEWNET) };
+
+                if rc != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let mut up = crate::up()?.collect::<Vec<_>>();
+                let links = netlink::links()?;
+
+                for ifa in &mut up {
+                    let link =
+                        links.iter().find(|link| link.index == ifa.index);
+                    ifa.speed = None;
+                    ifa.mtu = link.and_then(|link| link.mtu);
+                    ifa.hw_type = link.map_or(0, |link| link.hw_type);
+                    ifa.oper_status = link
+                        .and_then(|link| link.operstate)
+                        .map_or(OperStatus::Unknown, operstate);
+                }
+
+                Ok(up)
+            });
+
+            let up =
+                up.join().unwrap_or_else(|err| panic::resume_unwind(err))?;
+
+            let netns = NetnsId::Named(name);
+            ifas.extend(up.into_iter().map(|ifa| (netns.clone(), ifa)));
+        }
+
+        Ok(ifas)
+    }
+
+    /// An entry from a bridge's forwarding database, see [`bridge_fdb()`].
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    pub struct FdbEntry {
+        /// MAC address.
+        pub mac: MacAddr,
+        /// Name of the bridge port that the MAC address is reachable on.
+        pub port: String,
+        /// Neighbor state, NUD_* flags, e.g., NUD_PERMANENT for static
+        /// entries.
+        pub state: u16,
+        /// Neighbor flags, NTF_* flags, e.g., NTF_SELF for entries of the
+        /// port's own hardware database.
+        pub flags: u8,
+    }
+
+    /// Returns the forwarding database of bridge |name|, i.e., the MAC
+    /// addresses that the bridge learned or was configured with and the
+    /// ports they are reachable on. Linux only.
+    pub fn bridge_fdb(name: &str) -> io::Result<Vec<FdbEntry>> {
+        let index = index_of(name)?;
+
+        let entries = netlink::fdb()?
+            .into_iter()
+            .filter(|fdb| fdb.master == Some(index) || fdb.index == index)
+            .filter_map(|fdb| {
+                Some(FdbEntry {
+                    mac: MacAddr::new(fdb.mac),
+                    // Not found when the port went away in the meantime.
+                    port: index_to_name(fdb.index)?,
+                    state: fdb.state,
+                    flags: fdb.flags,
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// State of a bonding slave, see [`bond_slaves()`].
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    pub struct SlaveInfo {
+        /// Interface name of the slave.
+        pub name: String,
+        /// True if the slave is active, false if it is a backup.
+        pub active: bool,
+        /// True if the slave's link is up according to the MII monitor.
+        pub up: bool,
+        /// Number of times the slave's link went down.
+        pub link_failure_count: u32,
+    }
+
+    /// Returns the slaves of bonding interface |name| and their state.
+    /// Fails with io::ErrorKind::NotFound if |name| isn't a bond.
+    /// Linux only.
+    pub fn bond_slaves(name: &str) -> io::Result<Vec<SlaveInfo>> {
+        let slaves = fs::read_to_string(sysfs_path(name, "bonding/slaves")?)?;
+
+        let read = |name: &str, key: &str| -> io::Result<String> {
+            let path = sysfs_path(name, &format!("bonding_slave/{key}"))?;
+            Ok(fs::read_to_string(path)?.trim().to_owned())
+        };
+
+        slaves
+            .split_whitespace()
+            .map(|name| {
+                let link_failure_count = read(name, "link_failure_count")?;
+                let link_failure_count =
+                    link_failure_count.parse().unwrap_or(0);
+
+                Ok(SlaveInfo {
+                    name: name.to_owned(),
+                    active: read(name, "state")? == "active",
+                    up: read(name, "mii_status")? == "up",
+                    link_failure_count,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns /sys/class/net/<name>/<key>. Rejects names that would make
+    /// the path point elsewhere.
+    fn sysfs_path(name: &str, key: &str) -> io::Result<String> {
+        if name.is_empty() || name == "." || name == ".." || name.contains('/')
+        {
+            let msg = "invalid interface name";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        Ok(format!("/sys/class/net/{name}/{key}"))
+    }
+
+    /// Reads a numeric per-interface sysctl, e.g., sysctl("ipv6", "eth0",
+    /// "autoconf") reads /proc/sys/net/ipv6/conf/eth0/autoconf.
+    fn sysctl(family: &str, name: &str, key: &str) -> Option<i64> {
+        // Aliases like "eth0:1" share the configuration of "eth0".
+        let name = name.split(':').next()?;
+        let path = format!("/proc/sys/net/{family}/conf/{name}/{key}");
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    pub(crate) fn forwarding_enabled(name: &str) -> Option<(bool, bool)> {
+        let v4 = 0 != sysctl("ipv4", name, "forwarding")?;
+        // Missing when IPv6 is disabled, in which case it isn't forwarded.
+        let v6 = 0 != sysctl("ipv6", name, "forwarding").unwrap_or(0);
+        Some((v4, v6))
+    }
+
+    #[cfg(feature = "wifi")]
+    pub(crate) fn signal_strength(name: &str) -> io::Result<Option<i32>> {
+        let wireless = match fs::read_to_string("/proc/net/wireless") {
+            Ok(wireless) => wireless,
+            // Kernel built without wireless extensions.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(None)
+            }
+            Err(err) => return Err(err),
+        };
+
+        // Aliases like "eth0:1" share the link of "eth0".
+        let name = name.split(':').next().unwrap_or(name);
+
+        // Two header lines, then one line per wireless interface:
+        // " wlan0: 0000   70.  -40.  -256        0 ..."
+        //
+        // Drivers that report the level in dBm show it as a negative
+        // number, the kernel subtracts 256. A non-negative level is on
+        // a driver-specific scale that doesn't convert to dBm.
+        let level = wireless
+            .lines()
+            .skip(2)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(iface, _)| iface.trim() == name)
+            .and_then(|(_, rest)| rest.split_whitespace().nth(2))
+            .and_then(|level| level.trim_end_matches('.').parse().ok())
+            .filter(|&level: &i32| level < 0);
+
+        Ok(level)
+    }
+
+    pub(crate) fn ipv6_mtu(name: &str) -> Option<u32> {
+        sysctl("ipv6", name, "mtu")?.try_into().ok()
+    }
+
+    pub(crate) fn tempaddr_policy(name: &str) -> Option<i32> {
+        sysctl("ipv6", name, "use_tempaddr")?.try_into().ok()
+    }
+
+    pub(crate) fn netns_id(index: u32) -> Option<i32> {
+        let data = netlink::link_attr(index, netlink::IFLA_LINK_NETNSID);
+        let data = data.ok()??;
+        Some(i32::from_ne_bytes(data.get(..4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn anycast() -> io::Result<Vec<Interface>> {
+        let netmask = IpAddr::V6(Ipv6Addr::from(!0u128));
+
+        let ifas = netlink::anycast_addresses()?
+            .into_iter()
+            .filter_map(|addr| {
+                // Not found when the interface went away in the meantime.
+                let name = index_to_name(addr.index)?;
+                let scoped = crate::is_link_local(&addr.address);
+                let scope_id = if scoped { addr.index } else { 0 };
+                let mut ifa = Interface::new(name, addr.address, netmask)
+                    .with_index(addr.index)
+                    .with_scope_id(Some(scope_id));
+                ifa.anycast = true;
+                Some(ifa)
+            })
+            .collect();
+
+        Ok(ifas)
+    }
+
+    pub(crate) fn byte_count(name: &str) -> Option<u64> {
+        // Aliases like "eth0:1" share the statistics of "eth0".
+        let name = name.split(':').next()?;
+        let read = |key| {
+            let path = sysfs_path(name, key).ok()?;
+            fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+        };
+        Some(read("statistics/rx_bytes")? + read("statistics/tx_bytes")?)
+    }
+
+    pub(crate) fn kind(name: &str, hw_type: u16) -> InterfaceKind {
+        match hw_type {
+            c::ARPHRD_ETHER => {}
+            c::ARPHRD_LOOPBACK => return InterfaceKind::Loopback,
+            c::ARPHRD_IEEE80211..=c::ARPHRD_IEEE80211_RADIOTAP => {
+                return InterfaceKind::Wifi
+            }
+            c::ARPHRD_TUNNEL
+            | c::ARPHRD_TUNNEL6
+            | c::ARPHRD_SIT
+            | c::ARPHRD_IPGRE
+            | ARPHRD_IP6GRE
+            | c::ARPHRD_NONE => return InterfaceKind::Tunnel, // tun, wg
+            ARPHRD_RAWIP => return InterfaceKind::Cellular,
+            _ => return InterfaceKind::Other,
+        }
+
+        // Aliases like "eth0:1" share the link of "eth0".
+        let name = name.split(':').next().unwrap_or(name);
+        let uevent = sysfs_path(name, "uevent")
+            .and_then(fs::read_to_string)
+            .unwrap_or_default();
+
+        let devtype = uevent
+            .lines()
+            .find_map(|line| line.strip_prefix("DEVTYPE="));
+
+        match devtype {
+            Some("bridge") => InterfaceKind::Bridge,
+            Some("vlan") => InterfaceKind::Vlan,
+            Some("wlan") => InterfaceKind::Wifi,
+            Some("wwan") => InterfaceKind::Cellular,
+            _ => InterfaceKind::Ethernet,
+        }
+    }
+
+    /// Looks up the primary IPv4 address of interface |name| with a few
+    /// SIOCGIF* ioctls instead of walking the getifaddrs(3) list.
+    pub(crate) fn query(name: &str) -> io::Result<Option<Interface>> {
+        let mut req: c::ifreq = unsafe { mem::zeroed() };
+
+        // Leave room for the nul byte.
+        if name.len() >= req.ifr_name.len() || name.as_bytes().contains(&0) {
+            let msg = "invalid interface name";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        for (dst, &src) in req.ifr_name.iter_mut().zip(name.as_bytes()) {
+            *dst = src as c::c_char;
+        }
 
-    /// Returns an iterator that produces the list of interfaces that the
-    /// operating system considers "up", that is, configured and active.
-    pub fn up() -> io::Result<Up> {
-        let mut base = ptr::null_mut();
+        let fd = unsafe {
+            c::socket(c::AF_INET, c::SOCK_DGRAM | c::SOCK_CLOEXEC, 0)
+        };
 
-        if 0 != unsafe { c::getifaddrs(&mut base) } {
+        if fd < 0 {
             return Err(io::Error::last_os_error());
         }
 
-        let base = NonNull::new(base);
-        let iter = Iter(base);
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
 
-        Ok(Up { base, iter })
-    }
+        let ioctl = |request| -> io::Result<c::__c_anonymous_ifr_ifru> {
+            let mut req = req;
+            match unsafe { c::ioctl(fd.as_raw_fd(), request as _, &mut req) } {
+                0 => Ok(req.ifr_ifru),
+                _ => Err(io::Error::last_os_error()),
+            }
+        };
 
-    pub struct Up {
-        base: Option<NonNull<c::ifaddrs>>,
-        iter: Iter,
-    }
+        let ipv4 = |addr: c::sockaddr| {
+            let addr = &addr as *const _ as *const c::sockaddr_in;
+            let addr = unsafe { (*addr).sin_addr.s_addr };
+            IpAddr::V4(u32::from_be(addr).into())
+        };
 
-    impl Iterator for Up {
-        type Item = Interface;
+        let flags = unsafe { ioctl(c::SIOCGIFFLAGS)?.ifru_flags } as u16;
+        let flag = |flag: c::c_int| 0 != flags & flag as u16;
 
-        fn next(&mut self) -> Option<Self::Item> {
-            self.iter.find_map(|curr| to_interface(self.base, curr))
+        // Unlike up(), leave out interfaces that are down.
+        if !flag(c::IFF_UP) {
+            return Ok(None);
         }
-    }
 
-    impl Drop for Up {
-        fn drop(&mut self) {
-            if let Some(mut base) = self.base {
-                unsafe { c::freeifaddrs(base.as_mut()) };
+        let address = match ioctl(c::SIOCGIFADDR) {
+            Ok(ifru) => ipv4(unsafe { ifru.ifru_addr }),
+            Err(err) if err.raw_os_error() == Some(c::EADDRNOTAVAIL) => {
+                return Ok(None)
             }
-        }
-    }
+            Err(err) => return Err(err),
+        };
 
-    struct Iter(Option<NonNull<c::ifaddrs>>);
+        let netmask = ipv4(unsafe { ioctl(c::SIOCGIFNETMASK)?.ifru_netmask });
+        let mut ifa = Interface::new(name, address, netmask);
 
-    impl Iterator for Iter {
-        type Item = NonNull<c::ifaddrs>;
+        let hwaddr = unsafe { ioctl(c::SIOCGIFHWADDR)?.ifru_hwaddr };
+        ifa.hw_type = hwaddr.sa_family;
 
-        fn next(&mut self) -> Option<Self::Item> {
-            let curr = self.0?;
-            let next = unsafe { curr.as_ref().ifa_next };
-            mem::replace(&mut self.0, NonNull::new(next))
+        // SIOCGIFHWADDR doesn't report the address length.
+        if matches!(
+            ifa.hw_type,
+            c::ARPHRD_ETHER | c::ARPHRD_LOOPBACK | c::ARPHRD_IEEE80211
+        ) {
+            let mac = &hwaddr.sa_data[..6];
+            ifa.link_addr = mac.iter().map(|&b| b as u8).collect();
+            ifa.mac = ifa.link_addr[..].try_into().unwrap_or_default();
         }
-    }
 
-    fn ip(addr: NonNull<c::sockaddr>) -> Option<IpAddr> {
-        let family = unsafe { addr.as_ref().sa_family };
+        ifa.index = unsafe { ioctl(c::SIOCGIFINDEX)?.ifru_ifindex } as u32;
+        ifa.mtu = u32::try_from(unsafe { ioctl(c::SIOCGIFMTU)?.ifru_mtu }).ok();
+        ifa.flags = flags.into();
+        ifa.multicast = flag(c::IFF_MULTICAST);
 
-        match family as _ {
-            c::AF_INET => {
-                let addr = unsafe { &*(addr.as_ptr() as *mut c::sockaddr_in) };
-                let addr = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
-                Some(IpAddr::V4(addr))
-            }
-            c::AF_INET6 => {
-                let addr = unsafe { &*(addr.as_ptr() as *mut c::sockaddr_in6) };
-                let [b0, b1, b2, b3, b4, b5, b6, b7, b8, b9, b10, b11, b12, b13, b14, b15] =
-                    addr.sin6_addr.s6_addr;
-                let s0 = 256 * b0 as u16 + b1 as u16;
-                let s1 = 256 * b2 as u16 + b3 as u16;
-                let s2 = 256 * b4 as u16 + b5 as u16;
-                let s3 = 256 * b6 as u16 + b7 as u16;
-                let s4 = 256 * b8 as u16 + b9 as u16;
-                let s5 = 256 * b10 as u16 + b11 as u16;
-                let s6 = 256 * b12 as u16 + b13 as u16;
-                let s7 = 256 * b14 as u16 + b15 as u16;
-                let addr = Ipv6Addr::new(s0, s1, s2, s3, s4, s5, s6, s7);
-                Some(IpAddr::V6(addr))
-            }
-            _ => None,
+        if flag(c::IFF_POINTOPOINT) {
+            let dstaddr = unsafe { ioctl(c::SIOCGIFDSTADDR)?.ifru_dstaddr };
+            ifa.destination = Some(ipv4(dstaddr));
         }
-    }
 
-    fn to_interface(
-        base: Option<NonNull<c::ifaddrs>>,
-        curr: NonNull<c::ifaddrs>,
-    ) -> Option<Interface> {
-        let curr = unsafe { curr.as_ref() };
-        let addr = NonNull::new(curr.ifa_addr)?;
+        ifa.broadcast = None;
 
-        if is_link(addr) {
-            return None;
+        if flag(c::IFF_BROADCAST) && !flag(c::IFF_POINTOPOINT) {
+            let brdaddr = unsafe { ioctl(c::SIOCGIFBRDADDR)?.ifru_broadaddr };
+            ifa.broadcast = Some(ipv4(brdaddr));
         }
 
-        let address = ip(addr)?;
-        let netmask = NonNull::new(curr.ifa_netmask).and_then(ip)?;
+        // SIOCGIFFLAGS truncates the flags to 16 bits, IFF_DORMANT and
+        // IFF_LOWER_UP don't fit.
+        ifa.oper_status = match flag(c::IFF_RUNNING) {
+            true => OperStatus::Up,
+            false => OperStatus::LowerLayerDown,
+        };
 
-        let name = unsafe { CStr::from_ptr(curr.ifa_name) };
-        let mac = Iter(base)
-            .find_map(|link| mac_of(name, link))
-            .unwrap_or_default();
-        let name = name.to_string_lossy().into_owned();
+        Ok(Some(ifa))
+    }
 
-        let flags = From::from(curr.ifa_flags);
+    pub(crate) fn group(index: u32) -> Option<u32> {
+        let data = netlink::link_attr(index, netlink::IFLA_GROUP).ok()??;
+        Some(u32::from_ne_bytes(data.get(..4)?.try_into().ok()?))
+    }
 
-        let scope_id = address.is_ipv6().then(|| {
-            let addr = addr.as_ptr() as *const c::sockaddr_in6;
-            unsafe { (*addr).sin6_scope_id }
-        });
+    pub(crate) fn ra_flags(index: u32, name: &str) -> Option<RaFlags> {
+        let flags = netlink::inet6_flags(index).ok()??;
+        let managed = 0 != flags & netlink::IF_RA_MANAGED;
+        let other = 0 != flags & netlink::IF_RA_OTHERCONF;
+        let autoconf = 0 != sysctl("ipv6", name, "autoconf")?;
 
-        Some(Interface {
-            name,
-            flags,
-            mac,
-            address,
-            scope_id,
-            netmask,
+        Some(RaFlags {
+            managed,
+            other,
+            autoconf,
         })
     }
-}
 
-#[cfg(any(target_os = "android", target_os = "linux"))]
-mod linux {
-    use libc as c;
-    use std::ffi::CStr;
-    use std::ptr::NonNull;
+    /// Address attributes that getifaddrs(3) doesn't report.
+    pub(crate) struct AddrTable(Vec<netlink::Addr>);
+
+    impl AddrTable {
+        pub(crate) fn new() -> Self {
+            // Not fatal, the attributes are merely unavailable.
+            Self(netlink::addresses().unwrap_or_default())
+        }
+
+        pub(crate) fn empty() -> Self {
+            Self(Vec::new())
+        }
+
+        fn find(&self, index: u32, address: &IpAddr) -> Option<&netlink::Addr> {
+            self.0
+                .iter()
+                .find(|addr| addr.index == index && addr.address == *address)
+        }
+
+        pub(crate) fn is_secondary(
+            &self,
+            index: u32,
+            address: &IpAddr,
+        ) -> bool {
+            // IFA_F_SECONDARY doubles as IFA_F_TEMPORARY for IPv6 addresses.
+            address.is_ipv4()
+                && self.find(index, address).is_some_and(|addr| {
+                    0 != addr.flags & netlink::IFA_F_SECONDARY
+                })
+        }
+
+        pub(crate) fn is_tentative(
+            &self,
+            index: u32,
+            address: &IpAddr,
+        ) -> bool {
+            let mask = netlink::IFA_F_TENTATIVE | netlink::IFA_F_DADFAILED;
+            self.find(index, address)
+                .is_some_and(|addr| 0 != addr.flags & mask)
+        }
+
+        pub(crate) fn is_deprecated(
+            &self,
+            index: u32,
+            address: &IpAddr,
+        ) -> bool {
+            self.find(index, address)
+                .is_some_and(|addr| 0 != addr.flags & netlink::IFA_F_DEPRECATED)
+        }
+
+        pub(crate) fn scope(
+            &self,
+            index: u32,
+            address: &IpAddr,
+        ) -> Option<AddressScope> {
+            self.find(index, address).map(|addr| addr.scope.into())
+        }
+    }
 
     pub(crate) fn is_link(addr: NonNull<c::sockaddr>) -> bool {
         c::AF_PACKET == unsafe { addr.as_ref().sa_family } as _
     }
 
-    pub(crate) fn mac_of(
+    pub(crate) fn dstaddr(ifa: &c::ifaddrs) -> *mut c::sockaddr {
+        ifa.ifa_ifu
+    }
+
+    pub(crate) fn is_dormant(ifa: &c::ifaddrs) -> bool {
+        0 != ifa.ifa_flags & c::IFF_DORMANT as c::c_uint
+    }
+
+    /// Converts an IF_OPER_* state.
+    fn operstate(state: u8) -> OperStatus {
+        match state {
+            1 => OperStatus::NotPresent,
+            2 => OperStatus::Down,
+            3 => OperStatus::LowerLayerDown,
+            4 => OperStatus::Testing,
+            5 => OperStatus::Dormant,
+            6 => OperStatus::Up,
+            _ => OperStatus::Unknown,
+        }
+    }
+
+    pub(crate) fn oper_status(ifa: &c::ifaddrs) -> Option<OperStatus> {
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) }.to_str().ok()?;
+        // Aliases like "eth0:1" share the link of "eth0".
+        let name = name.split(':').next()?;
+        let path = sysfs_path(name, "operstate").ok()?;
+
+        let status = match fs::read_to_string(path).ok()?.trim() {
+            "up" => OperStatus::Up,
+            "down" => OperStatus::Down,
+            "testing" => OperStatus::Testing,
+            "dormant" => OperStatus::Dormant,
+            "notpresent" => OperStatus::NotPresent,
+            "lowerlayerdown" => OperStatus::LowerLayerDown,
+            _ => OperStatus::Unknown,
+        };
+
+        Some(status)
+    }
+
+    /// Reads the speed and MTU from sysfs when |live| is set.
+    pub(crate) fn link_of(
         name: &CStr,
         link: NonNull<c::ifaddrs>,
-    ) -> Option<[u8; 6]> {
+        live: bool,
+    ) -> Option<Link> {
         let link = unsafe { link.as_ref() };
         let addr = NonNull::new(link.ifa_addr)?;
 
@@ -436,33 +3775,152 @@ mod linux {
             return None;
         }
 
-        let addr = link.ifa_addr as *const _ as *const c::sockaddr_ll;
-        let addr = unsafe { &*addr };
+        let raw = link.ifa_addr as *const c::sockaddr_ll;
+        let addr = unsafe { &*raw };
+
+        // sll_addr is declared as an 8 byte array but glibc and musl both
+        // make room for 24 bytes, enough for e.g. InfiniBand addresses.
+        // Reading past the array through |addr| is out of bounds, go
+        // through the pointer that covers the whole allocation instead.
+        let len = (addr.sll_halen as usize).min(24);
+        let data = unsafe { std::ptr::addr_of!((*raw).sll_addr) };
+        let link_addr =
+            unsafe { std::slice::from_raw_parts(data as *const u8, len) };
+        let link_addr = link_addr.to_vec();
+
+        let index = addr.sll_ifindex as u32;
+        let hw_type = addr.sll_hatype;
+
+        let name = unsafe { CStr::from_ptr(link.ifa_name) }.to_str().ok();
+        let name = name.filter(|_| live);
+        let (speed, mtu) = name.map_or((None, None), speed_and_mtu);
+
+        Some(Link {
+            index,
+            link_addr,
+            hw_type,
+            speed,
+            mtu,
+        })
+    }
+
+    /// Fallback for interfaces without a link-layer entry. glibc leaves
+    /// it out when the interface has no hardware address, e.g., tun and
+    /// WireGuard devices.
+    pub(crate) fn link_by_name(name: &CStr) -> Option<Link> {
+        let index = unsafe { c::if_nametoindex(name.as_ptr()) };
 
-        if addr.sll_halen != 6 {
+        if index == 0 {
             return None;
         }
 
-        let [b0, b1, b2, b3, b4, b5, _, _] = addr.sll_addr;
+        // Aliases like "eth0:1" share the link of "eth0".
+        let name = name.to_str().ok()?.split(':').next()?;
+        let (speed, mtu) = speed_and_mtu(name);
 
-        Some([b0, b1, b2, b3, b4, b5])
+        let hw_type = sysfs_path(name, "type")
+            .and_then(fs::read_to_string)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0);
+
+        Some(Link {
+            index,
+            link_addr: Vec::new(),
+            hw_type,
+            speed,
+            mtu,
+        })
+    }
+
+    fn speed_and_mtu(name: &str) -> (Option<u64>, Option<u32>) {
+        let read = |key| -> Option<i64> {
+            let path = sysfs_path(name, key).ok()?;
+            fs::read_to_string(path).ok()?.trim().parse().ok()
+        };
+
+        // Reported in Mb/s, -1 or EINVAL when the link is down.
+        let speed = read("speed")
+            .and_then(|speed| u64::try_from(speed).ok())
+            .filter(|&speed| speed != 0)
+            .map(|speed| speed * 1_000_000);
+
+        let mtu = read("mtu").and_then(|mtu| u32::try_from(mtu).ok());
+
+        (speed, mtu)
     }
 }
 
 #[cfg(all(unix, not(any(target_os = "android", target_os = "linux"))))]
 mod bsd {
+    use crate::unix::Link;
+    use crate::AddressScope;
+    use crate::InterfaceKind;
+    use crate::OperStatus;
     use libc as c;
+    use std::collections::HashMap;
     use std::ffi::CStr;
+    use std::net::IpAddr;
+    use std::ptr;
     use std::ptr::NonNull;
 
+    /// Address attributes that getifaddrs(3) doesn't report.
+    pub(crate) struct AddrTable;
+
+    impl AddrTable {
+        pub(crate) fn new() -> Self {
+            Self
+        }
+
+        pub(crate) fn empty() -> Self {
+            Self
+        }
+
+        pub(crate) fn is_secondary(&self, _: u32, _: &IpAddr) -> bool {
+            false
+        }
+
+        pub(crate) fn is_tentative(&self, _: u32, _: &IpAddr) -> bool {
+            false
+        }
+
+        pub(crate) fn is_deprecated(&self, _: u32, _: &IpAddr) -> bool {
+            false
+        }
+
+        pub(crate) fn scope(&self, _: u32, _: &IpAddr) -> Option<AddressScope> {
+            None
+        }
+    }
+
     pub(crate) fn is_link(addr: NonNull<c::sockaddr>) -> bool {
         c::AF_LINK == unsafe { addr.as_ref().sa_family } as _
     }
 
-    pub(crate) fn mac_of(
+    pub(crate) fn dstaddr(ifa: &c::ifaddrs) -> *mut c::sockaddr {
+        ifa.ifa_dstaddr
+    }
+
+    pub(crate) fn is_dormant(_: &c::ifaddrs) -> bool {
+        false
+    }
+
+    /// Derived from the interface flags by the caller.
+    pub(crate) fn oper_status(_: &c::ifaddrs) -> Option<OperStatus> {
+        None
+    }
+
+    /// Every interface has an AF_LINK entry, no fallback needed.
+    pub(crate) fn link_by_name(_: &CStr) -> Option<Link> {
+        None
+    }
+
+    /// The speed and MTU are part of the entry, |live| makes no difference.
+    pub(crate) fn link_of(
         name: &CStr,
         link: NonNull<c::ifaddrs>,
-    ) -> Option<[u8; 6]> {
+        _: bool,
+    ) -> Option<Link> {
         let link = unsafe { link.as_ref() };
         let addr = NonNull::new(link.ifa_addr)?;
 
@@ -483,9 +3941,10 @@ mod bsd {
         let addr = link.ifa_addr as *const _ as *const c::sockaddr_dl;
         let addr = unsafe { &*addr };
 
-        if addr.sdl_alen != 6 {
-            return None;
-        }
+        let index = From::from(addr.sdl_index);
+        let hw_type = From::from(addr.sdl_type);
+        let speed = speed(link);
+        let mtu = mtu(link);
 
         // sdl data contains both the if name and link-level address.
         // See: https://illumos.org/man/3socket/sockaddr_dl
@@ -498,10 +3957,447 @@ mod bsd {
             )
         };
 
-        if let [b0, b1, b2, b3, b4, b5] = data[start..end] {
-            Some([b0, b1, b2, b3, b4, b5])
-        } else {
-            None
+        let link_addr = data[start..end].to_vec();
+
+        Some(Link {
+            index,
+            link_addr,
+            hw_type,
+            speed,
+            mtu,
+        })
+    }
+
+    /// The IFT_* constants mostly follow the IANA ifType numbering but the
+    /// tunnel types differ between Apple platforms and the other BSDs.
+    pub(crate) fn kind(hw_type: u16) -> InterfaceKind {
+        match hw_type {
+            0x06 => InterfaceKind::Ethernet, // IFT_ETHER
+            0x18 => InterfaceKind::Loopback, // IFT_LOOP
+            0x47 => InterfaceKind::Wifi,     // IFT_IEEE80211
+            0x83 => InterfaceKind::Tunnel,   // IFT_TUNNEL
+            0x87 => InterfaceKind::Vlan,     // IFT_L2VLAN
+            0xd1 => InterfaceKind::Bridge,   // IFT_BRIDGE
+            #[cfg(any(target_os = "ios", target_os = "macos"))]
+            0x37 | 0x39 => InterfaceKind::Tunnel, // IFT_GIF, IFT_STF
+            #[cfg(any(target_os = "ios", target_os = "macos"))]
+            0xff => InterfaceKind::Cellular, // IFT_CELLULAR
+            #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+            0xd7 | 0xf0 => InterfaceKind::Tunnel, // IFT_STF, IFT_GIF
+            _ => InterfaceKind::Other,
+        }
+    }
+
+    /// Reads the byte counters of every interface from the struct if_data
+    /// of its AF_LINK entry, keyed by interface name.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    ))]
+    pub(crate) fn byte_counts() -> HashMap<String, u64> {
+        let mut base = ptr::null_mut();
+        let mut counts = HashMap::new();
+
+        if 0 != unsafe { c::getifaddrs(&mut base) } {
+            return counts;
+        }
+
+        let mut curr = base;
+
+        while let Some(link) = unsafe { curr.as_ref() } {
+            curr = link.ifa_next;
+
+            let Some(addr) = NonNull::new(link.ifa_addr) else {
+                continue;
+            };
+
+            if !is_link(addr) || link.ifa_data.is_null() {
+                continue;
+            }
+
+            let name = unsafe { CStr::from_ptr(link.ifa_name) };
+            let name = name.to_string_lossy().into_owned();
+            let data = unsafe { &*(link.ifa_data as *const c::if_data) };
+            #[allow(clippy::useless_conversion)] // u32 on Apple platforms.
+            let bytes = u64::from(data.ifi_ibytes) + u64::from(data.ifi_obytes);
+            counts.insert(name, bytes);
+        }
+
+        unsafe { c::freeifaddrs(base) };
+
+        counts
+    }
+
+    #[cfg(not(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    )))]
+    pub(crate) fn byte_counts() -> HashMap<String, u64> {
+        HashMap::new()
+    }
+
+    /// Reads the link speed from the AF_LINK entry's struct if_data.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    ))]
+    fn speed(link: &c::ifaddrs) -> Option<u64> {
+        let data = link.ifa_data as *const c::if_data;
+
+        if data.is_null() {
+            return None;
+        }
+
+        #[allow(clippy::useless_conversion)] // u32 on Apple platforms.
+        let speed = u64::from(unsafe { (*data).ifi_baudrate });
+        (speed != 0).then_some(speed)
+    }
+
+    // libc doesn't define struct if_data for the other BSDs.
+    #[cfg(not(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    )))]
+    fn speed(_: &c::ifaddrs) -> Option<u64> {
+        None
+    }
+
+    /// Reads the MTU from the AF_LINK entry's struct if_data.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    ))]
+    fn mtu(link: &c::ifaddrs) -> Option<u32> {
+        let data = link.ifa_data as *const c::if_data;
+
+        if data.is_null() {
+            return None;
+        }
+
+        #[allow(clippy::unnecessary_cast)] // c_ulong on DragonFly.
+        let mtu = unsafe { (*data).ifi_mtu } as u32;
+        (mtu != 0).then_some(mtu)
+    }
+
+    #[cfg(not(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    )))]
+    fn mtu(_: &c::ifaddrs) -> Option<u32> {
+        None
+    }
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    ))]
+    pub(crate) use sockio::query;
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub(crate) use route::Monitor;
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+    ))]
+    mod sockio {
+        use crate::Interface;
+        use crate::OperStatus;
+        use libc as c;
+        use std::io;
+        use std::mem;
+        use std::net::IpAddr;
+        use std::os::fd::AsRawFd;
+        use std::os::fd::FromRawFd;
+        use std::os::fd::OwnedFd;
+
+        // From <sys/sockio.h>, _IOWR('i', n, struct ifreq). NetBSD's
+        // struct ifreq is bigger, hence different request numbers, and
+        // isn't supported.
+        const SIOCGIFFLAGS: c::c_ulong = 0xc0206911;
+        const SIOCGIFADDR: c::c_ulong = 0xc0206921;
+        const SIOCGIFDSTADDR: c::c_ulong = 0xc0206922;
+        const SIOCGIFBRDADDR: c::c_ulong = 0xc0206923;
+        const SIOCGIFNETMASK: c::c_ulong = 0xc0206925;
+        #[cfg(not(target_os = "openbsd"))]
+        const SIOCGIFMTU: c::c_ulong = 0xc0206933;
+        #[cfg(target_os = "openbsd")]
+        const SIOCGIFMTU: c::c_ulong = 0xc020697e;
+
+        /// struct ifreq, which libc doesn't define on every BSD.
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        struct IfReq {
+            name: [c::c_char; c::IFNAMSIZ],
+            ifru: IfrIfru,
+        }
+
+        /// The members of the ifr_ifru union that query() uses. The pointer
+        /// gives it the size and alignment of the real thing.
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        union IfrIfru {
+            addr: c::sockaddr,
+            flags: c::c_short,
+            mtu: c::c_int,
+            _data: *mut c::c_char,
+        }
+
+        /// Looks up the primary IPv4 address of interface |name| with a few
+        /// SIOCGIF* ioctls instead of walking the getifaddrs(3) list.
+        pub(crate) fn query(name: &str) -> io::Result<Option<Interface>> {
+            let mut req: IfReq = unsafe { mem::zeroed() };
+
+            // Leave room for the nul byte.
+            if name.len() >= req.name.len() || name.as_bytes().contains(&0) {
+                let msg = "invalid interface name";
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+            }
+
+            for (dst, &src) in req.name.iter_mut().zip(name.as_bytes()) {
+                *dst = src as c::c_char;
+            }
+
+            let fd = unsafe { c::socket(c::AF_INET, c::SOCK_DGRAM, 0) };
+
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+            // macOS doesn't have SOCK_CLOEXEC.
+            let raw = fd.as_raw_fd();
+
+            if unsafe { c::fcntl(raw, c::F_SETFD, c::FD_CLOEXEC) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let ioctl = |request| -> io::Result<IfrIfru> {
+                let mut req = req;
+                match unsafe { c::ioctl(raw, request, &mut req) } {
+                    0 => Ok(req.ifru),
+                    _ => Err(io::Error::last_os_error()),
+                }
+            };
+
+            let ipv4 = |addr: c::sockaddr| {
+                let addr = &addr as *const _ as *const c::sockaddr_in;
+                let addr = unsafe { (*addr).sin_addr.s_addr };
+                IpAddr::V4(u32::from_be(addr).into())
+            };
+
+            let flags = unsafe { ioctl(SIOCGIFFLAGS)?.flags } as u16;
+            let flag = |flag: c::c_int| 0 != flags & flag as u16;
+
+            // Unlike up(), leave out interfaces that are down.
+            if !flag(c::IFF_UP) {
+                return Ok(None);
+            }
+
+            let address = match ioctl(SIOCGIFADDR) {
+                Ok(ifru) => ipv4(unsafe { ifru.addr }),
+                Err(err) if err.raw_os_error() == Some(c::EADDRNOTAVAIL) => {
+                    return Ok(None)
+                }
+                Err(err) => return Err(err),
+            };
+
+            let netmask = ipv4(unsafe { ioctl(SIOCGIFNETMASK)?.addr });
+            let mut ifa = Interface::new(name, address, netmask);
+
+            // There is no SIOCGIFINDEX on every BSD.
+            ifa.index = unsafe { c::if_nametoindex(req.name.as_ptr()) };
+            ifa.mtu = u32::try_from(unsafe { ioctl(SIOCGIFMTU)?.mtu }).ok();
+            ifa.flags = flags.into();
+            ifa.multicast = flag(c::IFF_MULTICAST);
+
+            if flag(c::IFF_POINTOPOINT) {
+                let dstaddr = unsafe { ioctl(SIOCGIFDSTADDR)?.addr };
+                ifa.destination = Some(ipv4(dstaddr));
+            }
+
+            ifa.broadcast = None;
+
+            if flag(c::IFF_BROADCAST) && !flag(c::IFF_POINTOPOINT) {
+                let brdaddr = unsafe { ioctl(SIOCGIFBRDADDR)?.addr };
+                ifa.broadcast = Some(ipv4(brdaddr));
+            }
+
+            ifa.oper_status = match flag(c::IFF_RUNNING) {
+                true => OperStatus::Up,
+                false => OperStatus::LowerLayerDown,
+            };
+
+            Ok(Some(ifa))
+        }
+    }
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    mod route {
+        use libc as c;
+        use std::io;
+        use std::os::fd::AsRawFd;
+        use std::os::fd::FromRawFd;
+        use std::os::fd::OwnedFd;
+        use std::os::fd::RawFd;
+        use std::time::Instant;
+
+        /// PF_ROUTE socket that receives the kernel's routing messages, see
+        /// route(4).
+        #[derive(Debug)]
+        pub(crate) struct Monitor(OwnedFd);
+
+        impl AsRawFd for Monitor {
+            fn as_raw_fd(&self) -> RawFd {
+                self.0.as_raw_fd()
+            }
+        }
+
+        impl Monitor {
+            pub(crate) fn new() -> io::Result<Self> {
+                let fd = unsafe {
+                    c::socket(c::PF_ROUTE, c::SOCK_RAW, c::AF_UNSPEC)
+                };
+
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+                // macOS doesn't have SOCK_CLOEXEC.
+                let raw = fd.as_raw_fd();
+
+                if unsafe { c::fcntl(raw, c::F_SETFD, c::FD_CLOEXEC) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                Ok(Monitor(fd))
+            }
+
+            /// Waits until |deadline|, or forever when None, for a message that
+            /// announces an address or interface change: RTM_NEWADDR,
+            /// RTM_DELADDR or RTM_IFINFO. Returns Ok(false) when the deadline
+            /// passes first. Reads all messages that are queued.
+            pub(crate) fn wait(
+                &self,
+                deadline: Option<Instant>,
+            ) -> io::Result<bool> {
+                let fd = self.0.as_raw_fd();
+
+                // Big enough for the headers, which is all that is looked at.
+                let mut buf = [0u8; 2048];
+
+                loop {
+                    // Rounds up, or poll() returns early and the caller spins.
+                    let timeout = deadline.map_or(-1, |deadline| {
+                        let timeout =
+                            deadline.saturating_duration_since(Instant::now());
+                        let millis = timeout.as_nanos().div_ceil(1_000_000);
+                        millis.min(c::c_int::MAX as u128) as c::c_int
+                    });
+
+                    let mut pfd = c::pollfd {
+                        fd,
+                        events: c::POLLIN,
+                        revents: 0,
+                    };
+
+                    match unsafe { c::poll(&mut pfd, 1, timeout) } {
+                        0 => return Ok(false),
+                        n if n < 0 => {
+                            let err = io::Error::last_os_error();
+
+                            if err.kind() == io::ErrorKind::Interrupted {
+                                continue;
+                            }
+
+                            return Err(err);
+                        }
+                        _ => {}
+                    }
+
+                    let mut changed = false;
+
+                    loop {
+                        let n = unsafe {
+                            c::recv(
+                                fd,
+                                buf.as_mut_ptr() as *mut _,
+                                buf.len(),
+                                c::MSG_DONTWAIT,
+                            )
+                        };
+
+                        if n < 0 {
+                            let err = io::Error::last_os_error();
+
+                            match err.kind() {
+                                io::ErrorKind::Interrupted => continue,
+                                io::ErrorKind::WouldBlock => break,
+                                // The kernel dropped messages, assume the worst.
+                                _ if err.raw_os_error() == Some(c::ENOBUFS) => {
+                                    changed = true;
+                                    continue;
+                                }
+                                _ => return Err(err),
+                            }
+                        }
+
+                        // One message per read. All routing messages start with
+                        // the length (u16), version (u8) and type (u8).
+                        if let Some(&ty) = buf[..n as usize].get(3) {
+                            changed |= matches!(
+                                ty as c::c_int,
+                                c::RTM_NEWADDR | c::RTM_DELADDR | c::RTM_IFINFO
+                            );
+                        }
+                    }
+
+                    if changed {
+                        return Ok(true);
+                    }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,89 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A MAC address, a.k.a., EUI-48. Formats as "aa:bb:cc:dd:ee:ff" and
+/// parses that as well as the "AA-BB-CC-DD-EE-FF" form that Windows uses.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    pub const fn new(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+
+    pub const fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// True for the all-zero address that interfaces without a MAC
+    /// address report.
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 6]
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+}
+
+impl From<MacAddr> for [u8; 6] {
+    fn from(mac: MacAddr) -> Self {
+        mac.0
+    }
+}
+
+impl PartialEq<[u8; 6]> for MacAddr {
+    fn eq(&self, other: &[u8; 6]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [b0, b1, b2, b3, b4, b5] = self.0;
+        write!(f, "{b0:02x}:{b1:02x}:{b2:02x}:{b3:02x}:{b4:02x}:{b5:02x}")
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = MacAddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sep = if s.contains('-') { '-' } else { ':' };
+        let mut octets = [0u8; 6];
+        let mut parts = s.split(sep);
+
+        for b in &mut octets {
+            let part = parts.next().ok_or(MacAddrParseError(()))?;
+
+            // from_str_radix() accepts a leading '+', hence the check.
+            if part.len() != 2 || !part.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err(MacAddrParseError(()));
+            }
+
+            *b = u8::from_str_radix(part, 16)
+                .map_err(|_| MacAddrParseError(()))?;
+        }
+
+        if parts.next().is_some() {
+            return Err(MacAddrParseError(()));
+        }
+
+        Ok(MacAddr(octets))
+    }
+}
+
+/// Error returned by `MacAddr::from_str()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MacAddrParseError(());
+
+impl fmt::Display for MacAddrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid MAC address syntax")
+    }
+}
+
+impl Error for MacAddrParseError {}
@@ -0,0 +1,755 @@
+// Bare-bones rtnetlink client. Implements just enough of the protocol to
+// dump the kernel's tables; getifaddrs(3) does the heavy lifting, this only
+// fills in the details that getifaddrs doesn't report.
+use libc as c;
+use std::io;
+use std::mem;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::os::fd::AsRawFd;
+use std::os::fd::RawFd;
+use std::ptr;
+use std::slice;
+use std::time::Duration;
+
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+const NLM_F_REQUEST: u16 = 1;
+const NLM_F_MULTI: u16 = 2;
+const NLM_F_DUMP: u16 = 0x300;
+
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_DELLINK: u16 = 17;
+const RTM_GETLINK: u16 = 18;
+const RTM_NEWADDR: u16 = 20;
+const RTM_DELADDR: u16 = 21;
+const RTM_GETADDR: u16 = 22;
+const RTM_NEWROUTE: u16 = 24;
+const RTM_GETROUTE: u16 = 26;
+const RTM_NEWNEIGH: u16 = 28;
+const RTM_GETNEIGH: u16 = 30;
+const RTM_GETANYCAST: u16 = 62;
+
+const RTMGRP_LINK: u32 = 0x1;
+const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MTU: u16 = 4;
+const IFLA_PROTINFO: u16 = 12;
+const IFLA_OPERSTATE: u16 = 16;
+const IFLA_INET6_FLAGS: u16 = 1;
+
+pub(crate) const IFLA_GROUP: u16 = 27;
+pub(crate) const IFLA_LINK_NETNSID: u16 = 37;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_ANYCAST: u16 = 5;
+const IFA_FLAGS: u16 = 8;
+
+const RTA_DST: u16 = 1;
+const RTA_OIF: u16 = 4;
+const RTA_TABLE: u16 = 15;
+
+const RTN_UNICAST: u8 = 1;
+
+const NDA_LLADDR: u16 = 2;
+const NDA_MASTER: u16 = 9;
+
+pub(crate) const IFA_F_SECONDARY: u32 = 0x01;
+pub(crate) const IFA_F_DADFAILED: u32 = 0x08;
+pub(crate) const IFA_F_DEPRECATED: u32 = 0x20;
+pub(crate) const IFA_F_TENTATIVE: u32 = 0x40;
+
+pub(crate) const IF_RA_OTHERCONF: u32 = 0x80;
+pub(crate) const IF_RA_MANAGED: u32 = 0x40;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Header {
+    len: u32,
+    ty: u16,
+    flags: u16,
+    seq: u32,
+    pid: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct IfAddrMsg {
+    family: u8,
+    prefixlen: u8,
+    flags: u8,
+    scope: u8,
+    index: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct IfInfoMsg {
+    family: u8,
+    pad: u8,
+    ty: u16,
+    index: i32,
+    flags: u32,
+    change: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RtMsg {
+    family: u8,
+    dst_len: u8,
+    src_len: u8,
+    tos: u8,
+    table: u8,
+    protocol: u8,
+    scope: u8,
+    ty: u8,
+    flags: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NdMsg {
+    family: u8,
+    pad1: u8,
+    pad2: u16,
+    index: i32,
+    state: u16,
+    flags: u8,
+    ty: u8,
+}
+
+/// An entry from the kernel's address table.
+pub(crate) struct Addr {
+    pub(crate) index: u32,
+    pub(crate) address: IpAddr,
+    pub(crate) flags: u32,
+    pub(crate) scope: u8,
+}
+
+/// Dumps the kernel's address table.
+pub(crate) fn addresses() -> io::Result<Vec<Addr>> {
+    dump_addresses(c::AF_UNSPEC, RTM_GETADDR, RTM_NEWADDR)
+}
+
+/// Dumps the kernel's IPv6 anycast address table.
+pub(crate) fn anycast_addresses() -> io::Result<Vec<Addr>> {
+    // Not a typo, the kernel answers with RTM_GETANYCAST messages.
+    dump_addresses(c::AF_INET6, RTM_GETANYCAST, RTM_GETANYCAST)
+}
+
+fn dump_addresses(
+    family: c::c_int,
+    request: u16,
+    response: u16,
+) -> io::Result<Vec<Addr>> {
+    let msg = IfAddrMsg {
+        family: family as _,
+        prefixlen: 0,
+        flags: 0,
+        scope: 0,
+        index: 0,
+    };
+
+    let mut addrs = Vec::new();
+
+    Socket::new()?.dump(request, bytes_of(&msg), |ty, payload| {
+        if ty == response {
+            addrs.extend(addr_of(payload));
+        }
+    })?;
+
+    Ok(addrs)
+}
+
+/// Decodes the payload of an RTM_NEWADDR or RTM_DELADDR message.
+fn addr_of(payload: &[u8]) -> Option<Addr> {
+    let msg = unsafe { read::<IfAddrMsg>(payload) }?;
+
+    let mut flags = From::from(msg.flags);
+    let mut address = None;
+    let mut local = None;
+
+    for (ty, data) in attrs(&payload[align(mem::size_of_val(&msg))..]) {
+        match ty {
+            IFA_ADDRESS | IFA_ANYCAST => address = ip(msg.family, data),
+            IFA_LOCAL => local = ip(msg.family, data),
+            IFA_FLAGS => flags = u32_of(data).unwrap_or(flags),
+            _ => {}
+        }
+    }
+
+    // IFA_LOCAL is the local address for point-to-point links,
+    // IFA_ADDRESS is the address of the peer.
+    let address = local.or(address)?;
+
+    Some(Addr {
+        index: msg.index,
+        address,
+        flags,
+        scope: msg.scope,
+    })
+}
+
+/// An entry from the kernel's link table.
+pub(crate) struct Link {
+    pub(crate) index: u32,
+    pub(crate) name: String,
+    /// IFF_* flags.
+    pub(crate) flags: u32,
+    /// ARPHRD_* type.
+    pub(crate) hw_type: u16,
+    pub(crate) mtu: Option<u32>,
+    /// IF_OPER_* state.
+    pub(crate) operstate: Option<u8>,
+}
+
+/// Dumps the kernel's link table.
+pub(crate) fn links() -> io::Result<Vec<Link>> {
+    let msg = IfInfoMsg {
+        family: c::AF_UNSPEC as _,
+        pad: 0,
+        ty: 0,
+        index: 0,
+        flags: 0,
+        change: 0,
+    };
+
+    let mut links = Vec::new();
+
+    Socket::new()?.dump(RTM_GETLINK, bytes_of(&msg), |ty, payload| {
+        if ty == RTM_NEWLINK {
+            links.extend(link_of(payload));
+        }
+    })?;
+
+    Ok(links)
+}
+
+/// Decodes the payload of an RTM_NEWLINK or RTM_DELLINK message.
+fn link_of(payload: &[u8]) -> Option<Link> {
+    let msg = unsafe { read::<IfInfoMsg>(payload) }?;
+
+    let all = || attrs(&payload[align(mem::size_of_val(&msg))..]);
+    let attr = |ty| all().find(|&(attr, _)| attr == ty).map(|(_, v)| v);
+
+    let name = attr(IFLA_IFNAME)?;
+    let name = name.split(|&b| b == 0).next().unwrap_or_default();
+
+    let mtu = attr(IFLA_MTU).and_then(|v| v.get(..4)?.try_into().ok());
+    let mtu = mtu.map(u32::from_ne_bytes);
+    let operstate = attr(IFLA_OPERSTATE).and_then(|v| v.first().copied());
+
+    Some(Link {
+        index: msg.index as u32,
+        name: String::from_utf8_lossy(name).into_owned(),
+        flags: msg.flags,
+        hw_type: msg.ty,
+        mtu,
+        operstate,
+    })
+}
+
+/// A change that the kernel announced to a [`Monitor`].
+pub(crate) enum Change {
+    NewLink(Link),
+    DelLink(Link),
+    NewAddr(Addr),
+    DelAddr(Addr),
+}
+
+/// Socket that receives the kernel's link and address change
+/// announcements.
+#[derive(Debug)]
+pub(crate) struct Monitor(Socket);
+
+impl AsRawFd for Monitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0 .0
+    }
+}
+
+impl Monitor {
+    pub(crate) fn new() -> io::Result<Self> {
+        let socket = Socket::new()?;
+
+        let mut addr: c::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = c::AF_NETLINK as _;
+        addr.nl_groups = RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR;
+
+        let len = mem::size_of_val(&addr) as c::socklen_t;
+        let addr = &addr as *const c::sockaddr_nl as *const c::sockaddr;
+
+        if unsafe { c::bind(socket.0, addr, len) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Monitor(socket))
+    }
+
+    /// Waits at most |timeout|, or forever when None, for announcements.
+    /// Returns Ok(None) when the timeout expires. Fails with ENOBUFS when
+    /// the kernel dropped announcements because the socket's receive
+    /// buffer was full.
+    pub(crate) fn recv(
+        &self,
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<Vec<Change>>> {
+        // Rounds up, or poll() returns early and the caller spins.
+        let timeout = timeout.map_or(-1, |timeout| {
+            let millis = timeout.as_nanos().div_ceil(1_000_000);
+            millis.min(c::c_int::MAX as u128) as c::c_int
+        });
+
+        let mut pfd = c::pollfd {
+            fd: self.0 .0,
+            events: c::POLLIN,
+            revents: 0,
+        };
+
+        match unsafe { c::poll(&mut pfd, 1, timeout) } {
+            0 => return Ok(None),
+            n if n < 0 => {
+                let err = io::Error::last_os_error();
+
+                if err.kind() == io::ErrorKind::Interrupted {
+                    return Ok(Some(Vec::new()));
+                }
+
+                return Err(err);
+            }
+            _ => {}
+        }
+
+        let mut changes = Vec::new();
+        let mut buf = vec![0u8; 32 << 10];
+
+        loop {
+            let n = unsafe {
+                c::recv(
+                    self.0 .0,
+                    buf.as_mut_ptr() as *mut _,
+                    buf.len(),
+                    c::MSG_DONTWAIT,
+                )
+            };
+
+            if n < 0 {
+                let err = io::Error::last_os_error();
+
+                match err.kind() {
+                    io::ErrorKind::Interrupted => continue,
+                    io::ErrorKind::WouldBlock => return Ok(Some(changes)),
+                    _ => return Err(err),
+                }
+            }
+
+            for (header, payload) in messages(&buf[..n as usize]) {
+                let change = match header.ty {
+                    RTM_NEWLINK => link_of(payload).map(Change::NewLink),
+                    RTM_DELLINK => link_of(payload).map(Change::DelLink),
+                    RTM_NEWADDR => addr_of(payload).map(Change::NewAddr),
+                    RTM_DELADDR => addr_of(payload).map(Change::DelAddr),
+                    _ => None,
+                };
+
+                changes.extend(change);
+            }
+        }
+    }
+}
+
+/// An entry from the kernel's routing tables.
+pub(crate) struct Route {
+    pub(crate) index: u32,
+    pub(crate) dst: IpAddr,
+    pub(crate) dst_len: u8,
+    pub(crate) table: u32,
+}
+
+/// Dumps the kernel's IPv6 unicast routes.
+pub(crate) fn routes6() -> io::Result<Vec<Route>> {
+    let msg = RtMsg {
+        family: c::AF_INET6 as _,
+        dst_len: 0,
+        src_len: 0,
+        tos: 0,
+        table: 0,
+        protocol: 0,
+        scope: 0,
+        ty: 0,
+        flags: 0,
+    };
+
+    let mut routes = Vec::new();
+
+    Socket::new()?.dump(RTM_GETROUTE, bytes_of(&msg), |ty, payload| {
+        if ty != RTM_NEWROUTE {
+            return;
+        }
+
+        let Some(msg) = (unsafe { read::<RtMsg>(payload) }) else {
+            return;
+        };
+
+        if msg.ty != RTN_UNICAST {
+            return;
+        }
+
+        let mut index = None;
+        let mut table = From::from(msg.table);
+        // Absent for the default route.
+        let mut dst = ip(msg.family, &[0; 16]);
+
+        for (ty, data) in attrs(&payload[align(mem::size_of_val(&msg))..]) {
+            match ty {
+                RTA_DST => dst = ip(msg.family, data),
+                RTA_OIF => index = u32_of(data),
+                RTA_TABLE => table = u32_of(data).unwrap_or(table),
+                _ => {}
+            }
+        }
+
+        if let (Some(index), Some(dst)) = (index, dst) {
+            let dst_len = msg.dst_len;
+            routes.push(Route {
+                index,
+                dst,
+                dst_len,
+                table,
+            });
+        }
+    })?;
+
+    Ok(routes)
+}
+
+/// An entry from a bridge's forwarding database.
+pub(crate) struct Fdb {
+    pub(crate) index: u32,
+    pub(crate) master: Option<u32>,
+    pub(crate) mac: [u8; 6],
+    pub(crate) state: u16,
+    pub(crate) flags: u8,
+}
+
+/// Dumps the forwarding databases of all bridges.
+pub(crate) fn fdb() -> io::Result<Vec<Fdb>> {
+    let msg = NdMsg {
+        family: c::AF_BRIDGE as _,
+        pad1: 0,
+        pad2: 0,
+        index: 0,
+        state: 0,
+        flags: 0,
+        ty: 0,
+    };
+
+    let mut entries = Vec::new();
+
+    Socket::new()?.dump(RTM_GETNEIGH, bytes_of(&msg), |ty, payload| {
+        if ty != RTM_NEWNEIGH {
+            return;
+        }
+
+        let Some(msg) = (unsafe { read::<NdMsg>(payload) }) else {
+            return;
+        };
+
+        let mut mac = None;
+        let mut master = None;
+
+        for (ty, data) in attrs(&payload[align(mem::size_of_val(&msg))..]) {
+            match ty {
+                NDA_LLADDR => mac = data.try_into().ok(),
+                NDA_MASTER => master = u32_of(data),
+                _ => {}
+            }
+        }
+
+        if let Some(mac) = mac {
+            entries.push(Fdb {
+                index: msg.index as u32,
+                master,
+                mac,
+                state: msg.state,
+                flags: msg.flags,
+            });
+        }
+    })?;
+
+    Ok(entries)
+}
+
+/// Returns the payload of attribute |ty| (IFLA_*) of interface |index|.
+pub(crate) fn link_attr(index: u32, ty: u16) -> io::Result<Option<Vec<u8>>> {
+    let msg = IfInfoMsg {
+        family: c::AF_UNSPEC as _,
+        pad: 0,
+        ty: 0,
+        index: index as i32,
+        flags: 0,
+        change: 0,
+    };
+
+    let mut result = None;
+
+    Socket::new()?.get(RTM_GETLINK, bytes_of(&msg), |msgtype, payload| {
+        if msgtype != RTM_NEWLINK {
+            return;
+        }
+
+        let Some(msg) = (unsafe { read::<IfInfoMsg>(payload) }) else {
+            return;
+        };
+
+        result = attrs(&payload[align(mem::size_of_val(&msg))..])
+            .find(|&(attr, _)| attr == ty)
+            .map(|(_, data)| data.to_vec());
+    })?;
+
+    Ok(result)
+}
+
+/// Returns the IPv6 link flags (IF_RA_* etc.) of interface |index|.
+pub(crate) fn inet6_flags(index: u32) -> io::Result<Option<u32>> {
+    let msg = IfInfoMsg {
+        family: c::AF_INET6 as _,
+        pad: 0,
+        ty: 0,
+        index: 0,
+        flags: 0,
+        change: 0,
+    };
+
+    let mut result = None;
+
+    // The inet6 link info can only be dumped, not queried per interface.
+    Socket::new()?.dump(RTM_GETLINK, bytes_of(&msg), |ty, payload| {
+        if ty != RTM_NEWLINK {
+            return;
+        }
+
+        let Some(msg) = (unsafe { read::<IfInfoMsg>(payload) }) else {
+            return;
+        };
+
+        if msg.index as u32 != index {
+            return;
+        }
+
+        let protinfo = attrs(&payload[align(mem::size_of_val(&msg))..])
+            .find(|&(ty, _)| ty == IFLA_PROTINFO);
+
+        if let Some((_, protinfo)) = protinfo {
+            result = attrs(protinfo)
+                .find(|&(ty, _)| ty == IFLA_INET6_FLAGS)
+                .and_then(|(_, data)| u32_of(data));
+        }
+    })?;
+
+    Ok(result)
+}
+
+#[derive(Debug)]
+struct Socket(c::c_int);
+
+impl Socket {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe {
+            c::socket(
+                c::AF_NETLINK,
+                c::SOCK_RAW | c::SOCK_CLOEXEC,
+                c::NETLINK_ROUTE,
+            )
+        };
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Socket(fd))
+    }
+
+    /// Sends a dump request and calls |f| with the type and payload of
+    /// every message in the response.
+    fn dump(
+        &self,
+        ty: u16,
+        msg: &[u8],
+        f: impl FnMut(u16, &[u8]),
+    ) -> io::Result<()> {
+        self.request(ty, NLM_F_DUMP, msg, f)
+    }
+
+    /// Sends a request for a single object and calls |f| with the type and
+    /// payload of the response.
+    fn get(
+        &self,
+        ty: u16,
+        msg: &[u8],
+        f: impl FnMut(u16, &[u8]),
+    ) -> io::Result<()> {
+        self.request(ty, 0, msg, f)
+    }
+
+    fn request(
+        &self,
+        ty: u16,
+        flags: u16,
+        msg: &[u8],
+        mut f: impl FnMut(u16, &[u8]),
+    ) -> io::Result<()> {
+        let seq = 1;
+
+        let header = Header {
+            len: (mem::size_of::<Header>() + msg.len()) as u32,
+            ty,
+            flags: NLM_F_REQUEST | flags,
+            seq,
+            pid: 0,
+        };
+
+        let mut req = bytes_of(&header).to_vec();
+        req.extend_from_slice(msg);
+
+        let n =
+            unsafe { c::send(self.0, req.as_ptr() as *const _, req.len(), 0) };
+
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut buf = vec![0u8; 32 << 10];
+
+        loop {
+            let n = unsafe {
+                c::recv(self.0, buf.as_mut_ptr() as *mut _, buf.len(), 0)
+            };
+
+            if n < 0 {
+                let err = io::Error::last_os_error();
+
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                return Err(err);
+            }
+
+            for (header, payload) in messages(&buf[..n as usize]) {
+                if header.seq != seq {
+                    continue;
+                }
+
+                match header.ty {
+                    NLMSG_DONE => return Ok(()),
+                    NLMSG_ERROR => {
+                        let errno = i32_of(payload).unwrap_or(0);
+
+                        if errno != 0 {
+                            return Err(io::Error::from_raw_os_error(-errno));
+                        }
+                    }
+                    ty => f(ty, payload),
+                }
+
+                // Single-part response, no NLMSG_DONE follows.
+                if header.flags & NLM_F_MULTI == 0 {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { c::close(self.0) };
+    }
+}
+
+/// Iterates over the (header, payload) pairs of the messages in |buf|.
+fn messages(mut buf: &[u8]) -> impl Iterator<Item = (Header, &[u8])> {
+    std::iter::from_fn(move || {
+        let header = unsafe { read::<Header>(buf) }?;
+        let len = header.len as usize;
+
+        if len < mem::size_of::<Header>() || len > buf.len() {
+            return None;
+        }
+
+        let payload = &buf[mem::size_of::<Header>()..len];
+        buf = &buf[align(len).min(buf.len())..];
+
+        Some((header, payload))
+    })
+}
+
+/// Iterates over the (type, payload) pairs of a list of route attributes.
+fn attrs(mut buf: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        let [l0, l1, t0, t1, ..] = *buf else {
+            return None;
+        };
+
+        let len = u16::from_ne_bytes([l0, l1]) as usize;
+        let ty = u16::from_ne_bytes([t0, t1]);
+
+        if len < 4 || len > buf.len() {
+            return None;
+        }
+
+        let data = &buf[4..len];
+        buf = &buf[align(len).min(buf.len())..];
+
+        Some((ty & NLA_TYPE_MASK, data))
+    })
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn ip(family: u8, data: &[u8]) -> Option<IpAddr> {
+    match (family as c::c_int, data.len()) {
+        (c::AF_INET, 4) => {
+            let octets: [u8; 4] = data.try_into().ok()?;
+            Some(IpAddr::V4(Ipv4Addr::from(octets)))
+        }
+        (c::AF_INET6, 16) => {
+            let octets: [u8; 16] = data.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+fn u32_of(data: &[u8]) -> Option<u32> {
+    Some(u32::from_ne_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+fn i32_of(data: &[u8]) -> Option<i32> {
+    Some(i32::from_ne_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+fn bytes_of<T: Copy>(val: &T) -> &[u8] {
+    let len = mem::size_of_val(val);
+    unsafe { slice::from_raw_parts(val as *const T as *const u8, len) }
+}
+
+/// Caller must ensure that any bit pattern is a valid T.
+unsafe fn read<T: Copy>(buf: &[u8]) -> Option<T> {
+    if buf.len() < mem::size_of::<T>() {
+        return None;
+    }
+
+    Some(ptr::read_unaligned(buf.as_ptr() as *const T))
+}
@@ -0,0 +1,215 @@
+use crate::all;
+use crate::up_with_scratch;
+use crate::Interface;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io;
+use std::mem;
+
+/// The interfaces at one point in time, see [`Snapshot::diff()`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Snapshot {
+    interfaces: Vec<Interface>,
+    scratch: Scratch,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of what [`all()`] returns.
+    pub fn new() -> io::Result<Self> {
+        let interfaces = all()?.collect();
+        let scratch = Scratch::default();
+        Ok(Snapshot {
+            interfaces,
+            scratch,
+        })
+    }
+
+    /// Takes a new snapshot in place of this one, reusing its allocations:
+    /// the list, the interface names and link-layer addresses and, on
+    /// Windows, the buffer that GetAdaptersAddresses() fills in. Leaves
+    /// the interface list alone on error but not the other allocations,
+    /// the next refresh makes new ones.
+    pub fn refresh(&mut self) -> io::Result<()> {
+        let scratch = mem::take(&mut self.scratch);
+        let mut up = up_with_scratch(true, None, scratch)?;
+        up.recycle(&mut self.interfaces);
+        self.interfaces.extend(up.by_ref());
+        self.scratch = up.into_scratch();
+        Ok(())
+    }
+
+    pub fn interfaces(&self) -> &[Interface] {
+        &self.interfaces
+    }
+
+    /// What changed between |old| and |new|. Reports removals first, then
+    /// modifications, then additions. Interfaces that come or go also
+    /// have their addresses reported.
+    pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Change> {
+        changes(&old.interfaces, &new.interfaces)
+    }
+}
+
+impl From<Vec<Interface>> for Snapshot {
+    fn from(interfaces: Vec<Interface>) -> Self {
+        let scratch = Scratch::default();
+        Snapshot {
+            interfaces,
+            scratch,
+        }
+    }
+}
+
+/// Allocations that [`Snapshot::refresh()`] hands to the next listing.
+/// Not part of the snapshot's value, hence every Scratch equals every other.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    /// Interfaces whose name and link_addr can be refilled.
+    pub(crate) spare: Vec<Interface>,
+    #[cfg(target_os = "windows")]
+    pub(crate) buf: Vec<usize>,
+}
+
+impl Clone for Scratch {
+    fn clone(&self) -> Self {
+        Scratch::default()
+    }
+}
+
+impl PartialEq for Scratch {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Scratch {}
+
+impl Hash for Scratch {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl fmt::Debug for Scratch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Scratch")
+    }
+}
+
+/// The name and link-layer address buffers of one of the interfaces in
+/// |spare|, emptied. New buffers when |spare| is empty.
+pub(crate) fn buffers(spare: &mut Vec<Interface>) -> (String, Vec<u8>) {
+    let Some(ifa) = spare.pop() else {
+        return Default::default();
+    };
+
+    let (mut name, mut link_addr) = (ifa.name, ifa.link_addr);
+    name.clear();
+    link_addr.clear();
+    (name, link_addr)
+}
+
+/// See [`Snapshot::diff()`]. Interfaces carry the first of their addresses.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Change {
+    InterfaceAdded(Interface),
+    InterfaceRemoved(Interface),
+    /// Flags, MAC address, MTU, speed or operational status changed.
+    InterfaceModified {
+        old: Interface,
+        new: Interface,
+    },
+    AddressAdded(Interface),
+    AddressRemoved(Interface),
+    /// Netmask, DAD state, label or such changed.
+    AddressModified {
+        old: Interface,
+        new: Interface,
+    },
+}
+
+pub(crate) fn changes(old: &[Interface], new: &[Interface]) -> Vec<Change> {
+    let same = |a: &Interface, b: &Interface| {
+        a.address == b.address && a.is_sibling_of(b)
+    };
+
+    let mut changes = Vec::new();
+
+    for a in old.iter().filter(|a| !new.iter().any(|b| same(a, b))) {
+        changes.push(Change::AddressRemoved(a.clone()));
+    }
+
+    for a in links(old).filter(|a| !new.iter().any(|b| a.is_sibling_of(b))) {
+        changes.push(Change::InterfaceRemoved(a.clone()));
+    }
+
+    for b in links(new) {
+        let a = old.iter().find(|a| a.is_sibling_of(b));
+
+        if let Some(a) = a.filter(|a| !link_eq(a, b)) {
+            let (old, new) = (a.clone(), b.clone());
+            changes.push(Change::InterfaceModified { old, new });
+        }
+    }
+
+    for b in new {
+        let a = old.iter().find(|a| same(a, b));
+
+        if let Some(a) = a.filter(|a| !address_eq(a, b)) {
+            let (old, new) = (a.clone(), b.clone());
+            changes.push(Change::AddressModified { old, new });
+        }
+    }
+
+    for b in links(new).filter(|b| !old.iter().any(|a| a.is_sibling_of(b))) {
+        changes.push(Change::InterfaceAdded(b.clone()));
+    }
+
+    for b in new.iter().filter(|b| !old.iter().any(|a| same(a, b))) {
+        changes.push(Change::AddressAdded(b.clone()));
+    }
+
+    changes
+}
+
+/// The first entry of every interface in |ifas|.
+fn links(ifas: &[Interface]) -> impl Iterator<Item = &Interface> {
+    ifas.iter()
+        .enumerate()
+        .filter(|&(i, a)| !ifas[..i].iter().any(|b| b.is_sibling_of(a)))
+        .map(|(_, a)| a)
+}
+
+/// Compares what |a| and |b| say about their interface.
+fn link_eq(a: &Interface, b: &Interface) -> bool {
+    link_name(a) == link_name(b)
+        && a.flags == b.flags
+        && a.link_addr == b.link_addr
+        && a.hw_type == b.hw_type
+        && a.multicast == b.multicast
+        && a.speed == b.speed
+        && a.dormant == b.dormant
+        && a.mtu == b.mtu
+        && a.oper_status == b.oper_status
+}
+
+/// Alias labels like "eth0:1" belong to the address, not the link.
+fn link_name(ifa: &Interface) -> &str {
+    ifa.name.split(':').next().unwrap_or_default()
+}
+
+/// Compares what |a| and |b| say about their address.
+pub(crate) fn address_eq(a: &Interface, b: &Interface) -> bool {
+    a.name == b.name
+        && a.address == b.address
+        && a.scope_id == b.scope_id
+        && a.netmask == b.netmask
+        && a.secondary == b.secondary
+        && a.metric == b.metric
+        && a.tentative == b.tentative
+        && a.deprecated == b.deprecated
+        && a.destination == b.destination
+        && a.broadcast == b.broadcast
+        && a.anycast == b.anycast
+        && a.kernel_scope == b.kernel_scope
+}
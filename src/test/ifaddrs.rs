@@ -0,0 +1,184 @@
+use crate::from_ifaddrs;
+use crate::InterfaceFlags;
+use crate::OperStatus;
+use libc as c;
+use std::ffi::CStr;
+use std::mem;
+use std::net::IpAddr;
+use std::ptr;
+
+/// Synthetic getifaddrs(3) list. Entries are linked in insertion order.
+struct List {
+    entries: Vec<c::ifaddrs>,
+    sockaddrs: Vec<c::sockaddr_storage>,
+}
+
+impl List {
+    fn new() -> Self {
+        // Entries point into each other, the vectors must not reallocate.
+        let entries = Vec::with_capacity(16);
+        let sockaddrs = Vec::with_capacity(32);
+        List { entries, sockaddrs }
+    }
+
+    fn sockaddr(&mut self, addr: IpAddr) -> *mut c::sockaddr {
+        assert!(self.sockaddrs.len() < self.sockaddrs.capacity());
+        self.sockaddrs.push(unsafe { mem::zeroed() });
+        let storage = self.sockaddrs.last_mut().unwrap();
+
+        match addr {
+            IpAddr::V4(addr) => {
+                let sin = storage as *mut _ as *mut c::sockaddr_in;
+                let sin = unsafe { &mut *sin };
+                sin.sin_family = c::AF_INET as _;
+                sin.sin_addr.s_addr = u32::from(addr).to_be();
+            }
+            IpAddr::V6(addr) => {
+                let sin6 = storage as *mut _ as *mut c::sockaddr_in6;
+                let sin6 = unsafe { &mut *sin6 };
+                sin6.sin6_family = c::AF_INET6 as _;
+                sin6.sin6_addr.s6_addr = addr.octets();
+            }
+        }
+
+        storage as *mut _ as *mut c::sockaddr
+    }
+
+    fn push(
+        &mut self,
+        name: &'static CStr,
+        flags: c::c_int,
+        address: IpAddr,
+        netmask: Option<IpAddr>,
+    ) -> &mut c::ifaddrs {
+        assert!(self.entries.len() < self.entries.capacity());
+
+        let mut ifa: c::ifaddrs = unsafe { mem::zeroed() };
+        ifa.ifa_name = name.as_ptr() as *mut _;
+        ifa.ifa_flags = flags as _;
+        ifa.ifa_addr = self.sockaddr(address);
+        ifa.ifa_netmask = match netmask {
+            Some(netmask) => self.sockaddr(netmask),
+            None => ptr::null_mut(),
+        };
+
+        self.entries.push(ifa);
+        let len = self.entries.len();
+
+        if len > 1 {
+            let next = &mut self.entries[len - 1] as *mut _;
+            self.entries[len - 2].ifa_next = next;
+        }
+
+        self.entries.last_mut().unwrap()
+    }
+
+    fn head(&mut self) -> *mut c::ifaddrs {
+        self.entries
+            .first_mut()
+            .map_or(ptr::null_mut(), |ifa| ifa as *mut _)
+    }
+}
+
+#[test]
+fn test_from_ifaddrs() {
+    let mut list = List::new();
+    let name = c"test0";
+    let flags = c::IFF_UP | c::IFF_BROADCAST;
+    let v4 = "192.0.2.1".parse().unwrap();
+    let mask4 = "255.255.255.0".parse().unwrap();
+    let v6 = "2001:db8::1".parse().unwrap();
+    let mask6 = "ffff:ffff:ffff:ffff::".parse().unwrap();
+    list.push(name, flags, v4, Some(mask4));
+    list.push(name, flags, v6, Some(mask6));
+
+    let ifas = unsafe { from_ifaddrs(list.head()) }.collect::<Vec<_>>();
+    assert_eq!(ifas.len(), 2);
+
+    assert_eq!(ifas[0].name(), "test0");
+    assert_eq!(ifas[0].address(), &v4);
+    assert_eq!(ifas[0].cidr(), (&v4, 24));
+    assert_eq!(ifas[0].prefix_len(), 24);
+    assert_eq!(ifas[0].to_cidr().to_string(), "192.0.2.1/24");
+    assert_eq!(ifas[0].raw_flags(), flags as u64);
+    let expected = InterfaceFlags::UP | InterfaceFlags::BROADCAST;
+    assert_eq!(ifas[0].flags(), expected);
+    assert_eq!(ifas[0].scope_id(), None);
+
+    assert_eq!(ifas[1].address(), &v6);
+    assert_eq!(ifas[1].cidr(), (&v6, 64));
+    assert_eq!(ifas[1].scope_id(), Some(0));
+
+    // Empty list.
+    assert_eq!(unsafe { from_ifaddrs(ptr::null_mut()) }.count(), 0);
+}
+
+#[test]
+fn test_from_ifaddrs_offline() {
+    // Named after an interface that exists on the host, whose details
+    // mustn't leak into the synthetic entry.
+    let Some(host) = crate::up().unwrap().next() else {
+        return;
+    };
+
+    let name = std::ffi::CString::new(host.name()).unwrap();
+    let name: &'static CStr = Box::leak(name.into_boxed_c_str());
+
+    let mut list = List::new();
+    let flags = c::IFF_UP | c::IFF_RUNNING;
+    let v4 = "192.0.2.1".parse().unwrap();
+    let mask4 = "255.255.255.0".parse().unwrap();
+    list.push(name, flags, v4, Some(mask4));
+
+    let ifas = unsafe { from_ifaddrs(list.head()) }.collect::<Vec<_>>();
+    assert_eq!(ifas.len(), 1);
+    assert_eq!(ifas[0].name(), host.name());
+    assert_eq!(ifas[0].index(), 0);
+    assert_eq!(ifas[0].mtu(), None);
+    assert_eq!(ifas[0].speed(), None);
+    assert_eq!(ifas[0].mac_opt(), None);
+    assert_eq!(ifas[0].oper_status(), OperStatus::Up);
+}
+
+#[test]
+fn test_no_netmask() {
+    let mut list = List::new();
+    let name = c"ppp0";
+    let flags = c::IFF_UP | c::IFF_POINTOPOINT;
+    let v4 = "192.0.2.1".parse().unwrap();
+    let v6 = "2001:db8::1".parse().unwrap();
+    list.push(name, flags, v4, None);
+    list.push(name, flags, v6, None);
+
+    let ifas = unsafe { from_ifaddrs(list.head()) }.collect::<Vec<_>>();
+    assert_eq!(ifas.len(), 2);
+    assert_eq!(ifas[0].cidr(), (&v4, 32));
+    assert_eq!(ifas[1].cidr(), (&v6, 128));
+}
+
+#[test]
+fn test_cycle() {
+    let mut list = List::new();
+    let name = c"test0";
+    let flags = c::IFF_UP;
+    let a = "192.0.2.1".parse().unwrap();
+    let b = "192.0.2.2".parse().unwrap();
+    let c = "192.0.2.3".parse().unwrap();
+    let mask = "255.255.255.0".parse().unwrap();
+    list.push(name, flags, a, Some(mask));
+    list.push(name, flags, b, Some(mask));
+    let next = list.push(name, flags, c, Some(mask)) as *mut _;
+    // a -> b -> c -> c -> ...
+    list.entries[2].ifa_next = next;
+
+    let ifas = unsafe { from_ifaddrs(list.head()) }.collect::<Vec<_>>();
+    let addrs = ifas.iter().map(|ifa| *ifa.address()).collect::<Vec<_>>();
+    assert_eq!(addrs, [a, b, c]);
+
+    // a -> b -> c -> a -> ...
+    list.entries[2].ifa_next = list.head();
+
+    let ifas = unsafe { from_ifaddrs(list.head()) }.collect::<Vec<_>>();
+    let addrs = ifas.iter().map(|ifa| *ifa.address()).collect::<Vec<_>>();
+    assert_eq!(addrs, [a, b, c]);
+}
@@ -0,0 +1,39 @@
+use crate::netlink;
+use crate::up;
+use crate::watch;
+use std::os::fd::AsRawFd;
+
+/// Make sure netlink and getifaddrs agree on the addresses and indices.
+#[test]
+fn test_netlink_addresses() {
+    let addrs = netlink::addresses().unwrap();
+
+    for interface in up().unwrap() {
+        let found = addrs.iter().any(|addr| {
+            addr.index == interface.index()
+                && addr.address == *interface.address()
+        });
+        assert!(found, "interface: {:#?}", interface);
+    }
+}
+
+/// Make sure netlink and getifaddrs agree on the link names and indices.
+#[test]
+fn test_netlink_links() {
+    let links = netlink::links().unwrap();
+
+    for interface in up().unwrap() {
+        let found = links.iter().any(|link| {
+            link.index == interface.index()
+                && interface.name().starts_with(&link.name)
+        });
+        assert!(found, "interface: {:#?}", interface);
+    }
+}
+
+#[test]
+fn test_watcher_fd() {
+    let watcher = watch().unwrap();
+    let fd = watcher.as_raw_fd();
+    assert!(unsafe { libc::fcntl(fd, libc::F_GETFD) } >= 0);
+}
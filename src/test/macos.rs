@@ -11,7 +11,7 @@ fn test_mac_addresses() {
             continue;
         }
 
-        let mac = mac_hex(interface.mac());
+        let mac = interface.mac().to_string();
         let macos_mac = ifconfig_mac(interface.name()).expect("mac");
         assert_eq!(mac, macos_mac, "interface: {:#?}", interface);
     }
@@ -33,14 +33,3 @@ fn ifconfig_mac(if_name: &str) -> Option<String> {
         .map(|x| x.to_string())
         .next();
 }
-
-fn mac_hex(mac: [u8; 6]) -> String {
-    use std::fmt::Write;
-
-    let mut s = String::new();
-    write!(s, "{:02x}", mac[0]).unwrap();
-    for byte in &mac[1..] {
-        write!(s, ":{byte:02x}").unwrap();
-    }
-    s
-}
@@ -1,2 +1,799 @@
+#[cfg(not(target_os = "windows"))]
+mod ifaddrs;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod linux;
+
 #[cfg(target_os = "macos")]
 mod macos;
+
+use crate::all;
+use crate::by_name;
+use crate::capture_to_writer;
+use crate::devices;
+use crate::dump;
+use crate::enumerate;
+use crate::enumerator::glob;
+use crate::enumerator::sort;
+use crate::find_by_address;
+use crate::load;
+use crate::names;
+use crate::netplan_section;
+use crate::query;
+use crate::restore_from_reader;
+use crate::same_subnet;
+use crate::up;
+use crate::up_v4;
+use crate::up_v6;
+use crate::wait_for;
+use crate::watch;
+use crate::watch::coalesce;
+use crate::watch::diff;
+use crate::AddressScope;
+use crate::Change;
+use crate::Cidr;
+use crate::Event;
+use crate::Family;
+use crate::Interface;
+use crate::InterfaceFlags;
+use crate::InterfaceKind;
+use crate::MacAddr;
+use crate::OperStatus;
+use crate::Scope;
+use crate::Snapshot;
+use std::sync::mpsc::TryRecvError;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+#[test]
+fn test_same_subnet() {
+    let a = "192.168.1.42".parse().unwrap();
+    let b = "192.168.1.200".parse().unwrap();
+    let c = "192.168.2.1".parse().unwrap();
+    assert!(same_subnet(&a, &b, 24));
+    assert!(!same_subnet(&a, &c, 24));
+    assert!(same_subnet(&a, &c, 16));
+    assert!(same_subnet(&a, &c, 0));
+
+    let a = "2001:db8::1".parse().unwrap();
+    let b = "2001:db8::ffff:1".parse().unwrap();
+    let c = "2001:db8:0:1::1".parse().unwrap();
+    assert!(same_subnet(&a, &b, 64));
+    assert!(!same_subnet(&a, &c, 64));
+    assert!(same_subnet(&a, &c, 48));
+    assert!(same_subnet(&a, &a, 128));
+
+    let v4 = "10.0.0.1".parse().unwrap();
+    assert!(!same_subnet(&a, &v4, 0));
+}
+
+#[test]
+fn test_broadcast() {
+    let broadcast = |address: &str, netmask: &str| {
+        let address = address.parse().unwrap();
+        let netmask = netmask.parse().unwrap();
+        Interface::new("eth0", address, netmask).broadcast()
+    };
+
+    let expected = "192.168.1.255".parse().ok();
+    assert_eq!(broadcast("192.168.1.5", "255.255.255.0"), expected);
+    let expected = "10.255.255.255".parse().ok();
+    assert_eq!(broadcast("10.1.2.3", "255.0.0.0"), expected);
+    assert_eq!(broadcast("10.0.0.0", "255.255.255.254"), None);
+    assert_eq!(broadcast("10.0.0.1", "255.255.255.255"), None);
+    assert_eq!(broadcast("2001:db8::1", "ffff:ffff:ffff:ffff::"), None);
+}
+
+#[test]
+fn test_wildcard_mask() {
+    let wildcard_mask = |netmask: &str| {
+        let address = "2001:db8::1".parse().unwrap();
+        let netmask = netmask.parse().unwrap();
+        Interface::new("eth0", address, netmask)
+            .wildcard_mask()
+            .to_string()
+    };
+
+    for (netmask, expected) in [
+        ("255.255.255.0", "0.0.0.255"),
+        ("255.255.252.0", "0.0.3.255"),
+        ("255.255.255.255", "0.0.0.0"),
+        ("0.0.0.0", "255.255.255.255"),
+        ("ffff:ffff:ffff:ffff::", "::ffff:ffff:ffff:ffff"),
+        ("::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+    ] {
+        assert_eq!(wildcard_mask(netmask), expected, "{netmask}");
+    }
+}
+
+#[test]
+fn test_summary() {
+    let summary = |address: &str, netmask: &str, index| {
+        let address = address.parse().unwrap();
+        let netmask = netmask.parse().unwrap();
+        let ifa = Interface::new("eth0", address, netmask).with_index(index);
+        ifa.summary().to_string()
+    };
+
+    for (address, netmask, index, expected) in [
+        ("192.168.0.42", "255.255.255.0", 2, "eth0#2 192.168.0.42/24"),
+        ("10.0.0.1", "255.255.255.255", 0, "eth0#0 10.0.0.1/32"),
+        ("fe80::1", "ffff:ffff:ffff:ffff::", 7, "eth0#7 fe80::1/64"),
+    ] {
+        assert_eq!(summary(address, netmask, index), expected);
+    }
+}
+
+#[test]
+fn test_zone_id() {
+    let zone_id = |address: &str, scope_id| {
+        let address = address.parse().unwrap();
+        let netmask = "ffff:ffff:ffff:ffff::".parse().unwrap();
+        let ifa = Interface::new("eth0", address, netmask);
+        ifa.with_scope_id(scope_id).zone_id()
+    };
+
+    for (address, scope_id, expected) in [
+        ("fe80::1", Some(7), Some(7)),
+        ("fe80::1", Some(0), Some(0)),
+        ("fe80::1", None, None),
+        ("febf::1", Some(7), Some(7)),
+        ("fec0::1", Some(7), None),
+        ("2001:db8::1", Some(0), None),
+        ("::1", Some(0), None),
+        ("169.254.0.1", Some(7), None),
+    ] {
+        assert_eq!(zone_id(address, scope_id), expected, "{address}");
+    }
+}
+
+#[test]
+fn test_to_netplan_yaml() {
+    let address = "192.168.0.42".parse().unwrap();
+    let netmask = "255.255.255.0".parse().unwrap();
+    let ifa = Interface::new("eth0", address, netmask);
+    let expected = "network:\n  version: 2\n  ethernets:\n    eth0:\n      \
+                    addresses:\n        - 192.168.0.42/24\n";
+    assert_eq!(ifa.to_netplan_yaml(), expected);
+
+    for (name, expected) in [
+        ("Wi-Fi 2", r#""Wi-Fi 2""#),
+        ("a\"b\\c", r#""a\"b\\c""#),
+        ("tab\tnl\n", r#""tab\tnl\n""#),
+        ("del\u{7f}\u{1}", r#""del\x7f\x01""#),
+        ("it's", r#""it's""#),
+        ("café", r#""café""#),
+    ] {
+        let ifa = Interface::new(name, address, netmask);
+        let expected = format!("\n    {expected}:\n");
+        assert!(ifa.to_netplan_yaml().contains(&expected), "{name:?}");
+    }
+
+    for (kind, expected) in [
+        (InterfaceKind::Ethernet, "ethernets"),
+        (InterfaceKind::Wifi, "wifis"),
+        (InterfaceKind::Vlan, "vlans"),
+        (InterfaceKind::Bridge, "bridges"),
+        (InterfaceKind::Tunnel, "tunnels"),
+        (InterfaceKind::Loopback, "ethernets"),
+        (InterfaceKind::Cellular, "ethernets"),
+        (InterfaceKind::Other, "ethernets"),
+    ] {
+        assert_eq!(netplan_section(kind), expected, "{kind:?}");
+    }
+}
+
+#[test]
+fn test_cidr() {
+    let cidr = |s: &str, prefix_len| Cidr::new(s.parse().unwrap(), prefix_len);
+
+    assert_eq!(cidr("192.168.0.42", 24).to_string(), "192.168.0.42/24");
+    assert_eq!(cidr("2001:db8::1", 64).to_string(), "2001:db8::1/64");
+
+    let network = |s, prefix_len| cidr(s, prefix_len).network().to_string();
+    assert_eq!(network("192.168.0.42", 24), "192.168.0.0");
+    assert_eq!(network("192.168.0.42", 32), "192.168.0.42");
+    assert_eq!(network("192.168.0.42", 0), "0.0.0.0");
+    assert_eq!(network("2001:db8::1", 64), "2001:db8::");
+    assert_eq!(network("2001:db8::1", 0), "::");
+}
+
+#[test]
+fn test_devices() {
+    let devices = devices().unwrap();
+    let count = devices.iter().map(|dev| dev.interfaces().len()).sum();
+    assert_eq!(up().unwrap().count(), count);
+
+    for dev in devices {
+        assert!(dev
+            .interfaces()
+            .iter()
+            .all(|ifa| ifa.index() == dev.index()));
+
+        for family in [Family::V4, Family::V6] {
+            let primary = dev.primary_address(family);
+            let any = dev.addresses().any(|addr| Family::of(addr) == family);
+            assert_eq!(primary.is_some(), any, "{dev:?}");
+            assert!(primary.is_none_or(|addr| Family::of(&addr) == family));
+        }
+    }
+}
+
+#[test]
+fn test_up_v4_v6() {
+    let v4 = up_v4().unwrap().collect::<Vec<_>>();
+    let v6 = up_v6().unwrap().collect::<Vec<_>>();
+    assert!(v4.iter().all(|ifa| ifa.address().is_ipv4()));
+    assert!(v6.iter().all(|ifa| ifa.address().is_ipv6()));
+    assert_eq!(v4.len() + v6.len(), up().unwrap().count());
+}
+
+#[test]
+fn test_watch() {
+    let mut watcher = watch().unwrap().debounce(Duration::from_secs(60));
+    let _ = watcher.next_timeout(Duration::ZERO).unwrap();
+    let _ = watcher.watch_timeout(Duration::ZERO).unwrap();
+
+    // Nothing is held back unless something changed in the meantime.
+    if let Some(deadline) = watcher.next_deadline() {
+        assert!(deadline > Instant::now());
+    }
+}
+
+#[test]
+fn test_wait_for() {
+    if let Some(ifa) = up().unwrap().find(|ifa| ifa.is_up()) {
+        let found = wait_for(ifa.name(), Duration::ZERO).unwrap();
+        assert!(found.is_some_and(|found| found.name() == ifa.name()));
+    }
+
+    let timeout = Duration::from_millis(10);
+    assert_eq!(wait_for("netif-test-nonexistent", timeout).unwrap(), None);
+}
+
+#[test]
+fn test_watch_channel() {
+    let rx = watch().unwrap().into_channel().unwrap();
+    let disconnected = Err(TryRecvError::Disconnected);
+    assert_ne!(rx.try_recv(), disconnected);
+}
+
+#[test]
+fn test_watch_diff() {
+    let mask = "255.255.255.0".parse().unwrap();
+    let a = Interface::new("eth0", "10.0.0.1".parse().unwrap(), mask);
+    let a = a.with_index(2);
+    let b = Interface::new("eth0", "10.0.0.2".parse().unwrap(), mask);
+    let b = b.with_index(2);
+
+    let one = [a.clone()];
+    let other = [b.clone()];
+    assert_eq!(diff(&one, &one), []);
+    assert_eq!(
+        diff(&one, &other),
+        [
+            Event::AddressRemoved(a.clone()),
+            Event::AddressAdded(b.clone())
+        ]
+    );
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let running = (libc::IFF_UP | libc::IFF_RUNNING) as u64;
+        let c = a.clone().with_flags(running);
+        let d = b.clone().with_flags(running);
+        let name = String::from("eth0");
+
+        assert_eq!(
+            diff(&[a.clone(), b.clone()], &[c.clone(), d.clone()]),
+            [Event::LinkUp { index: 2, name }]
+        );
+
+        let name = String::from("eth0");
+        assert_eq!(
+            diff(&[c, d], &one),
+            [
+                Event::AddressRemoved(b.with_flags(running)),
+                Event::LinkDown { index: 2, name }
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_coalesce() {
+    let mask = "255.255.255.0".parse().unwrap();
+    let a = Interface::new("eth0", "10.0.0.1".parse().unwrap(), mask);
+    let b = Interface::new("eth0", "10.0.0.2".parse().unwrap(), mask);
+    let c =
+        Interface::new("eth0", *a.address(), "255.255.0.0".parse().unwrap());
+    let up = || Event::LinkUp {
+        index: 2,
+        name: "eth0".into(),
+    };
+    let down = || Event::LinkDown {
+        index: 2,
+        name: "eth0".into(),
+    };
+
+    let added = Event::AddressAdded;
+    let removed = Event::AddressRemoved;
+
+    assert_eq!(coalesce([added(a.clone()), removed(a.clone())]), []);
+    assert_eq!(coalesce([removed(a.clone()), added(a.clone())]), []);
+
+    // Still doing duplicate address detection, or on a new access point.
+    let mut a2 = a.clone().with_mac([2, 0, 0, 0, 0, 1]);
+    a2.tentative = true;
+    assert_eq!(coalesce([removed(a.clone()), added(a2)]), []);
+
+    assert_eq!(coalesce([down(), up(), down()]), [down()]);
+    assert_eq!(
+        coalesce([removed(a.clone()), added(c.clone())]),
+        [removed(a.clone()), added(c.clone())]
+    );
+    assert_eq!(
+        coalesce([added(a.clone()), added(b.clone()), removed(a.clone())]),
+        [added(b.clone())]
+    );
+    assert_eq!(
+        coalesce([removed(a.clone()), added(c.clone()), removed(c)]),
+        [removed(a)]
+    );
+}
+
+#[test]
+fn test_snapshot_diff() {
+    let now = Snapshot::new().unwrap();
+    assert_eq!(Snapshot::diff(&now, &now), []);
+
+    let mask = "255.255.255.0".parse().unwrap();
+    let a = Interface::new("eth0", "10.0.0.1".parse().unwrap(), mask);
+    let a = a.with_index(2);
+    let b = Interface::new("eth0:1", "10.0.0.2".parse().unwrap(), mask);
+    let b = b.with_index(2);
+    let c = Interface::new("eth1", "10.0.1.1".parse().unwrap(), mask);
+    let c = c.with_index(3);
+
+    let old = Snapshot::from(vec![a.clone(), b.clone()]);
+    assert_eq!(Snapshot::diff(&old, &old), []);
+
+    // Only the label changed.
+    let b2 = Interface::new("eth0:2", *b.address(), mask).with_index(2);
+    let new = Snapshot::from(vec![a.clone(), b2.clone(), c.clone()]);
+    assert_eq!(
+        Snapshot::diff(&old, &new),
+        [
+            Change::AddressModified {
+                old: b.clone(),
+                new: b2.clone()
+            },
+            Change::InterfaceAdded(c.clone()),
+            Change::AddressAdded(c.clone()),
+        ]
+    );
+
+    // The alias is the first entry now but the link is the same.
+    let new = Snapshot::from(vec![b.clone()]);
+    assert_eq!(
+        Snapshot::diff(&old, &new),
+        [Change::AddressRemoved(a.clone())]
+    );
+
+    let a2 = a.clone().with_mac([2, 0, 0, 0, 0, 1]);
+    let new = Snapshot::from(vec![a2.clone()]);
+    assert_eq!(
+        Snapshot::diff(&old, &new),
+        [
+            Change::AddressRemoved(b.clone()),
+            Change::InterfaceModified { old: a, new: a2 },
+        ]
+    );
+
+    let new = Snapshot::from(vec![c.clone()]);
+    assert_eq!(
+        Snapshot::diff(&old, &new),
+        [
+            Change::AddressRemoved(old.interfaces()[0].clone()),
+            Change::AddressRemoved(b),
+            Change::InterfaceRemoved(old.interfaces()[0].clone()),
+            Change::InterfaceAdded(c.clone()),
+            Change::AddressAdded(c),
+        ]
+    );
+}
+
+#[test]
+fn test_snapshot_refresh() {
+    let mask = "255.0.0.0".parse().unwrap();
+    let a = Interface::new(
+        "not-a-real-interface",
+        "10.0.0.1".parse().unwrap(),
+        mask,
+    );
+    let a = a.with_mac([2, 0, 0, 0, 0, 1]);
+
+    // Refills the spare name and link_addr without leaking their contents.
+    let mut snapshot = Snapshot::from(vec![a.clone(), a.clone(), a]);
+    snapshot.refresh().unwrap();
+    let now = Snapshot::new().unwrap();
+    assert_eq!(snapshot.interfaces().len(), now.interfaces().len());
+    assert_eq!(Snapshot::diff(&now, &snapshot), []);
+
+    snapshot.refresh().unwrap();
+    assert_eq!(Snapshot::diff(&now, &snapshot), []);
+}
+
+#[test]
+fn test_count() {
+    for f in [all, up, up_v4, up_v6] {
+        let n = f().unwrap().collect::<Vec<_>>().len();
+        assert_eq!(n, f().unwrap().count());
+
+        let (lower, upper) = f().unwrap().size_hint();
+        assert!(lower <= n);
+        assert!(upper.is_none() || upper >= Some(n));
+    }
+}
+
+#[test]
+fn test_names() {
+    let names = names().unwrap();
+    assert!(!names.is_empty());
+
+    for ifa in up().unwrap().filter(|ifa| ifa.index() != 0) {
+        assert!(
+            names.iter().any(|(index, _)| *index == ifa.index()),
+            "{ifa:?}"
+        );
+    }
+}
+
+#[test]
+fn test_by_name() {
+    for ifa in up().unwrap() {
+        let ifas = by_name(ifa.name()).unwrap();
+        assert!(ifas.iter().all(|other| other.name() == ifa.name()));
+        assert!(ifas.iter().any(|other| other.address() == ifa.address()));
+    }
+
+    assert!(by_name("does-not-exist").unwrap().is_empty());
+}
+
+#[test]
+fn test_find_by_address() {
+    for ifa in up().unwrap() {
+        let found = find_by_address(*ifa.address()).unwrap().unwrap();
+        assert_eq!(found.address(), ifa.address());
+    }
+
+    let loopback = "127.1.2.3".parse().unwrap();
+    if let Some(ifa) = find_by_address(loopback).unwrap() {
+        assert!(ifa.address().is_loopback());
+    }
+
+    let other = "192.0.2.255".parse().unwrap();
+    if let Some(ifa) = find_by_address(other).unwrap() {
+        assert!(same_subnet(ifa.address(), &other, ifa.prefix_len()));
+    }
+}
+
+#[test]
+fn test_query() {
+    for ifa in up_v4().unwrap() {
+        let found = query(ifa.name()).unwrap().unwrap();
+        let ifas = by_name(ifa.name()).unwrap();
+        assert!(ifas.iter().any(|other| other.address() == found.address()));
+        assert_eq!(found.index(), ifa.index());
+    }
+
+    assert!(query("does-not-exist").is_err());
+    assert!(query("").is_err());
+}
+
+#[test]
+fn test_glob() {
+    assert!(glob("eth*", "eth0"));
+    assert!(glob("eth*", "eth"));
+    assert!(glob("e?h0", "eth0"));
+    assert!(glob("*", ""));
+    assert!(glob("*0", "eth0"));
+    assert!(glob("*t*0", "eth0"));
+    assert!(glob("Wi-Fi*", "Wi-Fi 2"));
+    assert!(glob("a*b*c", "aXbYbZc"));
+    assert!(!glob("eth*", "wlan0"));
+    assert!(!glob("eth?", "eth"));
+    assert!(!glob("eth0", "eth0:1"));
+    assert!(!glob("", "eth0"));
+    assert!(!glob("a*b*c", "aXbYbZ"));
+}
+
+#[test]
+fn test_sort() {
+    let ifa = |name: &str, address: &str, metric| {
+        let address = address.parse().unwrap();
+        let mut ifa = Interface::new(name, address, address);
+        ifa.metric = metric;
+        ifa
+    };
+
+    let expected = [
+        ifa("wlan0", "192.168.1.2", Some(10)),
+        ifa("eth0", "192.168.1.1", Some(25)),
+        ifa("eth0", "fe80::1", Some(25)),
+        ifa("eth0", "10.0.0.1", None),
+        ifa("eth0", "10.0.0.2", None),
+        ifa("eth1", "10.0.1.1", None),
+    ];
+
+    let mut ifas = expected.clone();
+    ifas.reverse();
+    sort(&mut ifas);
+    assert_eq!(ifas, expected);
+
+    let sorted = enumerate().sorted().run().unwrap();
+    let mut ifas = sorted.clone();
+    sort(&mut ifas);
+    assert_eq!(ifas, sorted);
+}
+
+#[test]
+fn test_enumerate() {
+    let ifas = enumerate().run().unwrap();
+    assert_eq!(ifas, up().unwrap().collect::<Vec<_>>());
+
+    let ifas = enumerate().ipv4_only().skip_loopback().run().unwrap();
+    assert!(ifas.iter().all(|ifa| ifa.address().is_ipv4()));
+    assert!(ifas.iter().all(|ifa| !ifa.address().is_loopback()));
+
+    let ifas = enumerate().name_matches("does-not-exist*").run().unwrap();
+    assert!(ifas.is_empty());
+}
+
+#[test]
+fn test_host_count() {
+    let host_count = |address: &str, netmask: &str| {
+        let address = address.parse().unwrap();
+        let netmask = netmask.parse().unwrap();
+        Interface::new("eth0", address, netmask).host_count()
+    };
+
+    assert_eq!(host_count("192.168.1.5", "255.255.255.0"), 254);
+    assert_eq!(host_count("10.0.0.1", "0.0.0.0"), (1 << 32) - 2);
+    assert_eq!(host_count("10.0.0.1", "255.255.255.252"), 2);
+    assert_eq!(host_count("10.0.0.1", "255.255.255.254"), 2);
+    assert_eq!(host_count("10.0.0.1", "255.255.255.255"), 1);
+    assert_eq!(host_count("2001:db8::1", "ffff:ffff:ffff:ffff::"), 1 << 64);
+    assert_eq!(
+        host_count("::1", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+        1
+    );
+    assert_eq!(host_count("2001:db8::1", "::"), u128::MAX);
+}
+
+#[test]
+fn test_mac_bits() {
+    let address = "192.0.2.1".parse().unwrap();
+
+    for (mac, local, multicast, oui) in [
+        (
+            [0, 0x1b, 0x21, 0, 0, 1],
+            false,
+            false,
+            Some([0, 0x1b, 0x21]),
+        ),
+        ([1, 0, 0x5e, 0, 0, 1], false, true, Some([1, 0, 0x5e])),
+        ([2, 0xfc, 0, 0, 0, 1], true, false, None),
+        ([0x33, 0x33, 0, 0, 0, 1], true, true, None),
+        ([0xff; 6], true, true, None),
+        ([0; 6], false, false, None),
+    ] {
+        let ifa = Interface::new("eth0", address, address).with_mac(mac);
+        assert_eq!(ifa.is_locally_administered(), local, "{mac:02x?}");
+        assert_eq!(ifa.is_multicast_mac(), multicast, "{mac:02x?}");
+        assert_eq!(ifa.oui(), oui, "{mac:02x?}");
+    }
+}
+
+#[test]
+fn test_address_scope() {
+    for (scope, expected) in [
+        (0, AddressScope::Global),
+        (200, AddressScope::Site),
+        (253, AddressScope::Link),
+        (254, AddressScope::Host),
+        (255, AddressScope::Nowhere),
+        (1, AddressScope::Other(1)),
+        (252, AddressScope::Other(252)),
+    ] {
+        assert_eq!(AddressScope::from(scope), expected, "{scope}");
+    }
+
+    for scope in 0..=u8::MAX {
+        assert_eq!(u8::from(AddressScope::from(scope)), scope);
+    }
+}
+
+#[test]
+fn test_scope_of() {
+    for (address, expected) in [
+        ("127.0.0.1", Scope::Loopback),
+        ("127.255.255.254", Scope::Loopback),
+        ("::1", Scope::Loopback),
+        ("169.254.0.1", Scope::LinkLocal),
+        ("fe80::1", Scope::LinkLocal),
+        ("10.0.0.1", Scope::Private),
+        ("172.16.0.1", Scope::Private),
+        ("192.168.0.1", Scope::Private),
+        ("fd00::1", Scope::Private),
+        ("8.8.8.8", Scope::Global),
+        ("172.32.0.1", Scope::Global),
+        ("2001:db8::1", Scope::Global),
+    ] {
+        let addr = address.parse().unwrap();
+        assert_eq!(Scope::of(&addr), expected, "{address}");
+    }
+}
+
+#[test]
+fn test_speed_string() {
+    let speed_string = |speed| {
+        let address = "10.0.0.1".parse().unwrap();
+        let netmask = "255.0.0.0".parse().unwrap();
+        let mut ifa = Interface::new("eth0", address, netmask);
+        ifa.speed = speed;
+        ifa.speed_string()
+    };
+
+    let s = |s: &str| Some(s.to_owned());
+    assert_eq!(speed_string(Some(10_000_000)), s("10 Mb/s"));
+    assert_eq!(speed_string(Some(100_000_000)), s("100 Mb/s"));
+    assert_eq!(speed_string(Some(1_000_000_000)), s("1 Gb/s"));
+    assert_eq!(speed_string(Some(2_500_000_000)), s("2.5 Gb/s"));
+    assert_eq!(speed_string(Some(10_000_000_000)), s("10 Gb/s"));
+    assert_eq!(speed_string(Some(9600)), s("9.6 kb/s"));
+    assert_eq!(speed_string(Some(300)), s("300 b/s"));
+    assert_eq!(speed_string(None), None);
+}
+
+#[test]
+fn test_mac_addr() {
+    let mac = MacAddr::new([0x02, 0xfc, 0x00, 0x0a, 0xbc, 0xff]);
+    assert_eq!(mac.to_string(), "02:fc:00:0a:bc:ff");
+    assert_eq!("02:fc:00:0a:bc:ff".parse(), Ok(mac));
+    assert_eq!("02-FC-00-0A-BC-FF".parse(), Ok(mac));
+    assert_eq!(mac, [0x02, 0xfc, 0x00, 0x0a, 0xbc, 0xff]);
+
+    for s in [
+        "",
+        "02:fc:00:0a:bc",
+        "02:fc:00:0a:bc:ff:00",
+        "2:fc:0:a:bc:ff",
+    ] {
+        assert!(s.parse::<MacAddr>().is_err(), "{s}");
+    }
+
+    for s in [
+        "02:fc:00:0a:bc:+f",
+        "02-fc:00:0a:bc:ff",
+        "02:fc:00:0a:bc:gg",
+    ] {
+        assert!(s.parse::<MacAddr>().is_err(), "{s}");
+    }
+}
+
+#[test]
+fn test_interface_flags() {
+    let flags = InterfaceFlags::UP | InterfaceFlags::MULTICAST;
+    assert!(flags.contains(InterfaceFlags::UP));
+    assert!(!flags.contains(InterfaceFlags::UP | InterfaceFlags::RUNNING));
+    assert!(InterfaceFlags::empty().is_empty());
+    assert_eq!(flags & InterfaceFlags::MULTICAST, InterfaceFlags::MULTICAST);
+    assert_eq!(format!("{flags:?}"), "InterfaceFlags(UP | MULTICAST)");
+    assert_eq!(format!("{:?}", InterfaceFlags::empty()), "InterfaceFlags()");
+
+    for ifa in all().unwrap() {
+        let up = ifa.flags().contains(InterfaceFlags::UP);
+        assert_eq!(up, ifa.is_up(), "{ifa:?}");
+
+        if ifa.address().is_loopback() {
+            assert!(ifa.flags().contains(InterfaceFlags::LOOPBACK), "{ifa:?}");
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        if !ifa.flags().contains(InterfaceFlags::BROADCAST) {
+            assert_eq!(ifa.broadcast(), None, "{ifa:?}");
+        }
+    }
+}
+
+#[test]
+fn test_mac_opt() {
+    let address = "192.0.2.1".parse().unwrap();
+    let ifa = Interface::new("eth0", address, address);
+    assert_eq!(ifa.mac_opt(), None);
+
+    let mac = [0x02, 0xfc, 0x00, 0x00, 0x00, 0x01];
+    assert_eq!(ifa.clone().with_mac(mac).mac_opt(), Some(mac.into()));
+    assert_eq!(ifa.with_mac([0; 6]).mac_opt(), Some(MacAddr::default()));
+}
+
+#[test]
+fn test_has_eui64_identifier() {
+    let mac = [0x02, 0xfc, 0x00, 0x00, 0x00, 0x01];
+    let netmask = "ffff:ffff:ffff:ffff::".parse().unwrap();
+
+    let slaac = "fe80::fc:ff:fe00:1".parse().unwrap();
+    let ifa = Interface::new("eth0", slaac, netmask).with_mac(mac);
+    assert!(ifa.has_eui64_identifier());
+    assert!(!ifa.clone().with_mac([0; 6]).has_eui64_identifier());
+
+    let random = "fe80::1234:5678:9abc:def0".parse().unwrap();
+    let ifa = Interface::new("eth0", random, netmask).with_mac(mac);
+    assert!(!ifa.has_eui64_identifier());
+
+    let v4 = "192.0.2.1".parse().unwrap();
+    let ifa = Interface::new("eth0", v4, v4).with_mac(mac);
+    assert!(!ifa.has_eui64_identifier());
+}
+
+#[test]
+fn test_dump_load() {
+    assert_eq!(
+        load(&dump().unwrap()).unwrap(),
+        up().unwrap().collect::<Vec<_>>()
+    );
+
+    let address = "fe80::1".parse().unwrap();
+    let netmask = "ffff:ffff:ffff:ffff::".parse().unwrap();
+    let mut ifa = Interface::new("Wi-Fi 2\t\\\n", address, netmask)
+        .with_index(7)
+        .with_scope_id(Some(7))
+        .with_mac([0xde, 0xad, 0xbe, 0xef, 0, 1]);
+    ifa.metric = Some(25);
+    ifa.lease_expiry = Some(SystemTime::UNIX_EPOCH + Duration::new(42, 7));
+    ifa.tentative = true;
+    ifa.deprecated = true;
+    ifa.destination = "fe80::2".parse().ok();
+    ifa.speed = Some(1_000_000_000);
+    ifa.dormant = true;
+    ifa.anycast = true;
+    ifa.mtu = Some(9000);
+    ifa.kernel_scope = Some(AddressScope::Link);
+    ifa.link_addr = (0..20).collect();
+    ifa.oper_status = OperStatus::LowerLayerDown;
+
+    let mut s = String::new();
+    crate::dump::write(&mut s, &ifa);
+    assert_eq!(s.lines().count(), 1);
+    assert_eq!(load(&s).unwrap()[0].lease_expiry(), ifa.lease_expiry());
+    assert_eq!(load(&s).unwrap(), vec![ifa]);
+
+    // No link_addr, it defaults to the MAC address.
+    let s = "name=x\taddress=1.2.3.4\tnetmask=255.0.0.0\tmac=02:00:00:00:00:01";
+    let mac = [2, 0, 0, 0, 0, 1];
+    assert_eq!(load(s).unwrap()[0].link_addr(), Some(&mac[..]));
+
+    assert!(load("name=x\taddress=1.2.3.4").is_err()); // No netmask.
+    assert!(load("name=x\taddress=1.2.3.4\tnetmask=bad").is_err());
+    assert_eq!(load("").unwrap(), vec![]);
+}
+
+#[test]
+fn test_capture_restore() {
+    let mut buf = Vec::new();
+    capture_to_writer(&mut buf).unwrap();
+    assert!(restore_from_reader(&buf[..]).is_ok());
+
+    let s = "netif-capture 1\nname=x\taddress=1.2.3.4\tnetmask=255.0.0.0\n";
+    let ifas = restore_from_reader(s.as_bytes()).unwrap();
+    assert_eq!(ifas.len(), 1);
+    assert_eq!(ifas[0].name(), "x");
+
+    let err = restore_from_reader("netif-capture 1\nname=x".as_bytes());
+    assert!(err.unwrap_err().to_string().starts_with("line 2:"));
+    assert!(restore_from_reader("netif-capture 2\n".as_bytes()).is_err());
+    assert!(restore_from_reader("name=x\taddress=1.2.3.4".as_bytes()).is_err());
+    assert!(restore_from_reader(&b""[..]).is_err());
+}
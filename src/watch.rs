@@ -0,0 +1,672 @@
+use crate::all;
+use crate::snapshot::address_eq;
+use crate::snapshot::changes;
+use crate::snapshot::Change;
+use crate::Interface;
+use crate::InterfaceFlags;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::netlink;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::collections::HashMap;
+
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+use crate::bsd;
+
+#[cfg(target_os = "windows")]
+use crate::names;
+#[cfg(target_os = "windows")]
+use crate::windows;
+#[cfg(target_os = "windows")]
+use std::collections::HashMap;
+#[cfg(target_os = "windows")]
+use std::net::IpAddr;
+
+/// How often the watcher lists the interfaces to look for changes on
+/// platforms that don't announce them.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+)))]
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Change to the interface configuration, see [`watch()`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// Address was added, or its interface was created or came back.
+    AddressAdded(Interface),
+    /// Address was removed, or its interface went away.
+    AddressRemoved(Interface),
+    /// Interface became operational.
+    LinkUp { index: u32, name: String },
+    /// Interface stopped being operational, e.g., the cable was pulled.
+    LinkDown { index: u32, name: String },
+}
+
+/// Watches the interface configuration for changes. Only reports changes
+/// that happen after the call, not the addresses that already exist; use
+/// [`all()`] for those.
+pub fn watch() -> io::Result<Watcher> {
+    // Subscribe before listing, or changes in between go unnoticed.
+    let backend = Backend::new()?;
+
+    Ok(Watcher {
+        state: all()?.collect(),
+        pending: VecDeque::new(),
+        ready: VecDeque::new(),
+        debounce: None,
+        last_change: Instant::now(),
+        backend,
+    })
+}
+
+/// See [`watch()`]. Iterating blocks until the next event.
+#[derive(Debug)]
+pub struct Watcher {
+    state: Vec<Interface>,
+    /// Events that [`Watcher::debounce()`] holds back.
+    pending: VecDeque<Event>,
+    ready: VecDeque<Event>,
+    debounce: Option<Duration>,
+    last_change: Instant,
+    backend: Backend,
+}
+
+impl Watcher {
+    /// Holds back events until nothing changed for |quiet|, then drops
+    /// the ones that cancel out, e.g., an address that was removed and
+    /// added back while Wi-Fi was roaming, or a link that went down and
+    /// up again. Makes a burst of changes produce one event per logical
+    /// change, at the cost of reporting them |quiet| later.
+    pub fn debounce(mut self, quiet: Duration) -> Self {
+        self.debounce = Some(quiet);
+        self
+    }
+
+    /// Waits at most |timeout| for the next event. Returns Ok(None) when
+    /// the timeout expires without anything changing.
+    pub fn next_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<Event>> {
+        let deadline = Instant::now().checked_add(timeout);
+
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Ok(Some(event));
+            }
+
+            let release = self.release();
+
+            if release.is_some_and(|release| release <= Instant::now()) {
+                let events = self.pending.drain(..);
+
+                match self.debounce {
+                    Some(_) => self.ready.extend(coalesce(events)),
+                    None => self.ready.extend(events),
+                }
+
+                continue;
+            }
+
+            let until = match (deadline, release) {
+                (Some(deadline), Some(release)) => Some(deadline.min(release)),
+                (deadline, release) => deadline.or(release),
+            };
+
+            let Watcher {
+                state,
+                pending,
+                backend,
+                ..
+            } = self;
+
+            let len = pending.len();
+            let woke = backend.wait(state, pending, until)?;
+
+            if pending.len() > len {
+                self.last_change = Instant::now();
+            }
+
+            let now = Instant::now();
+            let expired = deadline.is_some_and(|deadline| deadline <= now);
+            let due = release.is_some_and(|release| release <= now);
+
+            if !woke && expired && !due {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Same as [`Watcher::next_timeout()`], for callers that poll the
+    /// watcher from a loop that also does other work.
+    pub fn watch_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<Event>> {
+        self.next_timeout(timeout)
+    }
+
+    /// When [`Watcher::next_timeout()`] has events to return without
+    /// waiting for the operating system: the events that
+    /// [`Watcher::debounce()`] held back are due, or some are ready
+    /// already. None when there are none. The file descriptor or handle
+    /// doesn't become ready for those, so event loops should wake up no
+    /// later than that.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match self.ready.is_empty() {
+            true => self.release(),
+            false => Some(self.last_change), // In the past.
+        }
+    }
+
+    /// When the pending events can be let through. None is never.
+    fn release(&self) -> Option<Instant> {
+        match self.debounce {
+            _ if self.pending.is_empty() => None,
+            None => Some(self.last_change),
+            Some(quiet) => self.last_change.checked_add(quiet),
+        }
+    }
+
+    /// Moves the watcher to a thread of its own that sends the events
+    /// to the returned channel. The channel disconnects when the watcher
+    /// fails. After the receiver is dropped, the thread exits when the
+    /// next event comes in.
+    pub fn into_channel(self) -> io::Result<Receiver<Event>> {
+        let (tx, rx) = mpsc::channel();
+
+        thread::Builder::new().name("netif-watcher".into()).spawn(
+            move || {
+                for event in self.map_while(Result::ok) {
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                }
+            },
+        )?;
+
+        Ok(rx)
+    }
+}
+
+/// The netlink or PF_ROUTE socket, for use with epoll, kqueue, mio and
+/// such. When it is readable, or at [`Watcher::next_deadline()`], call
+/// [`Watcher::next_timeout()`] with a zero timeout until it returns
+/// Ok(None).
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+impl std::os::fd::AsRawFd for Watcher {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.backend.monitor.as_raw_fd()
+    }
+}
+
+/// Event object that is signaled when notifications are waiting, for use
+/// with WaitForMultipleObjects() and such. When it is signaled, or at
+/// [`Watcher::next_deadline()`], call [`Watcher::next_timeout()`] with a
+/// zero timeout until it returns Ok(None).
+#[cfg(target_os = "windows")]
+impl std::os::windows::io::AsRawHandle for Watcher {
+    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+        self.backend.notifier.event() as _
+    }
+}
+
+impl Iterator for Watcher {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_timeout(Duration::MAX).transpose()
+    }
+}
+
+/// Events that turn |old| into |new|: removed addresses first, then links
+/// that went up or down, then added addresses.
+pub(crate) fn diff(old: &[Interface], new: &[Interface]) -> Vec<Event> {
+    let event = |change| match change {
+        Change::AddressAdded(ifa) => Some(Event::AddressAdded(ifa)),
+        Change::AddressRemoved(ifa) => Some(Event::AddressRemoved(ifa)),
+        Change::InterfaceModified { old, new } => {
+            let running = (is_running(&old), is_running(&new));
+            let (index, name) = (new.index, new.name);
+
+            match running {
+                (false, true) => Some(Event::LinkUp { index, name }),
+                (true, false) => Some(Event::LinkDown { index, name }),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    changes(old, new).into_iter().filter_map(event).collect()
+}
+
+/// Drops the events in |events| that a later event undoes, e.g., an
+/// address that is added and removed again, or a link that goes down and
+/// up again.
+pub(crate) fn coalesce(events: impl IntoIterator<Item = Event>) -> Vec<Event> {
+    let mut result: Vec<Event> = Vec::new();
+
+    for event in events {
+        match result.iter().rposition(|prev| undoes(&event, prev)) {
+            Some(i) => drop(result.remove(i)),
+            None => result.push(event),
+        }
+    }
+
+    result
+}
+
+fn undoes(event: &Event, prev: &Event) -> bool {
+    match (prev, event) {
+        (Event::AddressAdded(a), Event::AddressRemoved(b)) => {
+            a.address == b.address && a.is_sibling_of(b)
+        }
+        // Unless it came back with a different netmask or such. An IPv6
+        // address is tentative again until DAD finishes.
+        (Event::AddressRemoved(a), Event::AddressAdded(b)) => {
+            let tentative = a.tentative;
+            let b = &Interface {
+                tentative,
+                ..b.clone()
+            };
+            a.is_sibling_of(b) && address_eq(a, b)
+        }
+        (Event::LinkUp { index: a, .. }, Event::LinkDown { index: b, .. })
+        | (Event::LinkDown { index: a, .. }, Event::LinkUp { index: b, .. }) => {
+            a == b
+        }
+        _ => false,
+    }
+}
+
+fn is_running(ifa: &Interface) -> bool {
+    ifa.flags().contains(InterfaceFlags::RUNNING)
+}
+
+/// Lists the interfaces every [`POLL_INTERVAL`] and diffs the lists.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+)))]
+#[derive(Debug)]
+struct Backend {
+    next_poll: Instant,
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+)))]
+impl Backend {
+    fn new() -> io::Result<Self> {
+        let next_poll = Instant::now() + POLL_INTERVAL;
+        Ok(Backend { next_poll })
+    }
+
+    /// Waits until |deadline|, or forever when None, for changes and
+    /// appends the events to |pending|. Returns Ok(false) when the
+    /// deadline passes first. Can return Ok(true) without events.
+    fn wait(
+        &mut self,
+        state: &mut Vec<Interface>,
+        pending: &mut VecDeque<Event>,
+        deadline: Option<Instant>,
+    ) -> io::Result<bool> {
+        let now = Instant::now();
+
+        if now >= self.next_poll {
+            let new: Vec<_> = all()?.collect();
+            pending.extend(diff(state, &new));
+            *state = new;
+            self.next_poll = Instant::now() + POLL_INTERVAL;
+            return Ok(true);
+        }
+
+        let until = match deadline {
+            Some(deadline) if deadline <= now => return Ok(false),
+            Some(deadline) => deadline.min(self.next_poll),
+            None => self.next_poll,
+        };
+
+        thread::sleep(until - now);
+
+        Ok(true)
+    }
+}
+
+/// Lists the interfaces and diffs the lists when the kernel announces
+/// a change on a PF_ROUTE socket. The messages don't carry enough to
+/// build an Interface from, and their layout differs between the BSDs.
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+#[derive(Debug)]
+struct Backend {
+    monitor: bsd::Monitor,
+}
+
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+impl Backend {
+    fn new() -> io::Result<Self> {
+        let monitor = bsd::Monitor::new()?;
+        Ok(Backend { monitor })
+    }
+
+    /// Waits until |deadline|, or forever when None, for changes and
+    /// appends the events to |pending|. Returns Ok(false) when the
+    /// deadline passes first. Can return Ok(true) without events.
+    fn wait(
+        &mut self,
+        state: &mut Vec<Interface>,
+        pending: &mut VecDeque<Event>,
+        deadline: Option<Instant>,
+    ) -> io::Result<bool> {
+        if !self.monitor.wait(deadline)? {
+            return Ok(false);
+        }
+
+        let new: Vec<_> = all()?.collect();
+        pending.extend(diff(state, &new));
+        *state = new;
+
+        Ok(true)
+    }
+}
+
+/// Decodes the kernel's RTM_NEWADDR, RTM_DELADDR and RTM_NEWLINK
+/// announcements.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Debug)]
+struct Backend {
+    monitor: netlink::Monitor,
+    /// Whether the link with that index is running, because announcements
+    /// carry the current flags but not what changed.
+    running: HashMap<u32, bool>,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl Backend {
+    fn new() -> io::Result<Self> {
+        let monitor = netlink::Monitor::new()?;
+        let running = running()?;
+        Ok(Backend { monitor, running })
+    }
+
+    /// Waits until |deadline|, or forever when None, for changes and
+    /// appends the events to |pending|. Returns Ok(false) when the
+    /// deadline passes first. Can return Ok(true) without events.
+    fn wait(
+        &mut self,
+        state: &mut Vec<Interface>,
+        pending: &mut VecDeque<Event>,
+        deadline: Option<Instant>,
+    ) -> io::Result<bool> {
+        let timeout = deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let changes = match self.monitor.recv(timeout) {
+            Ok(Some(changes)) => changes,
+            Ok(None) => return Ok(false),
+            Err(err) if err.raw_os_error() == Some(libc::ENOBUFS) => {
+                self.resync(state, pending)?;
+                return Ok(true);
+            }
+            Err(err) => return Err(err),
+        };
+
+        // Announcements don't carry everything that Interface has. List
+        // the interfaces once when an address appears and look it up.
+        let mut new: Option<Vec<Interface>> = None;
+
+        let is = |ifa: &Interface, addr: &netlink::Addr| {
+            ifa.index == addr.index && ifa.address == addr.address
+        };
+
+        for change in changes {
+            match change {
+                netlink::Change::NewAddr(addr) => {
+                    if state.iter().any(|ifa| is(ifa, &addr)) {
+                        continue; // Flags changed, e.g., DAD finished.
+                    }
+
+                    let new = match &mut new {
+                        Some(new) => new,
+                        None => new.insert(all()?.collect()),
+                    };
+
+                    if let Some(ifa) = new.iter().find(|ifa| is(ifa, &addr)) {
+                        state.push(ifa.clone());
+                        pending.push_back(Event::AddressAdded(ifa.clone()));
+                    }
+                }
+                netlink::Change::DelAddr(addr) => {
+                    if let Some(i) = state.iter().position(|ifa| is(ifa, &addr))
+                    {
+                        let ifa = state.remove(i);
+                        pending.push_back(Event::AddressRemoved(ifa));
+                    }
+                }
+                netlink::Change::NewLink(link) => {
+                    let running = 0 != link.flags & libc::IFF_RUNNING as u32;
+                    let (index, name) = (link.index, link.name);
+
+                    match self.running.insert(index, running) {
+                        Some(false) if running => {
+                            pending.push_back(Event::LinkUp { index, name })
+                        }
+                        Some(true) if !running => {
+                            pending.push_back(Event::LinkDown { index, name })
+                        }
+                        _ => {}
+                    }
+                }
+                netlink::Change::DelLink(link) => {
+                    let (index, name) = (link.index, link.name);
+
+                    if self.running.remove(&index) == Some(true) {
+                        pending.push_back(Event::LinkDown { index, name });
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Starts over after the kernel dropped announcements.
+    fn resync(
+        &mut self,
+        state: &mut Vec<Interface>,
+        pending: &mut VecDeque<Event>,
+    ) -> io::Result<()> {
+        let new: Vec<_> = all()?.collect();
+        pending.extend(diff(state, &new));
+        *state = new;
+        self.running = running()?;
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn running() -> io::Result<HashMap<u32, bool>> {
+    let links = netlink::links()?.into_iter();
+    let running = |flags| 0 != flags & libc::IFF_RUNNING as u32;
+    Ok(links
+        .map(|link| (link.index, running(link.flags)))
+        .collect())
+}
+
+/// Translates the notifications of NotifyIpInterfaceChange() and
+/// NotifyUnicastIpAddressChange().
+#[cfg(target_os = "windows")]
+#[derive(Debug)]
+struct Backend {
+    notifier: windows::Notifier,
+    /// Whether the interface with that index is operational, because
+    /// interface notifications don't say what changed.
+    running: HashMap<u32, bool>,
+}
+
+#[cfg(target_os = "windows")]
+impl Backend {
+    fn new() -> io::Result<Self> {
+        let notifier = windows::Notifier::new()?;
+        let running = running()?;
+        Ok(Backend { notifier, running })
+    }
+
+    /// Waits until |deadline|, or forever when None, for changes and
+    /// appends the events to |pending|. Returns Ok(false) when the
+    /// deadline passes first. Can return Ok(true) without events.
+    fn wait(
+        &mut self,
+        state: &mut Vec<Interface>,
+        pending: &mut VecDeque<Event>,
+        deadline: Option<Instant>,
+    ) -> io::Result<bool> {
+        let timeout = deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let Some(notifications) = self.notifier.recv(timeout)? else {
+            return Ok(false);
+        };
+
+        // Notifications only carry the index and address. List the
+        // interfaces once when an address appears and look it up.
+        let mut new: Option<Vec<Interface>> = None;
+
+        let is = |ifa: &Interface, index: u32, address: IpAddr| {
+            ifa.index == index && ifa.address == address
+        };
+
+        for notification in notifications {
+            match notification {
+                windows::Notification::NewAddr(index, address) => {
+                    if state.iter().any(|ifa| is(ifa, index, address)) {
+                        continue;
+                    }
+
+                    let new = match &mut new {
+                        Some(new) => new,
+                        None => new.insert(all()?.collect()),
+                    };
+
+                    let found = new.iter().find(|ifa| is(ifa, index, address));
+
+                    if let Some(ifa) = found {
+                        state.push(ifa.clone());
+                        pending.push_back(Event::AddressAdded(ifa.clone()));
+                    }
+                }
+                windows::Notification::DelAddr(index, address) => {
+                    let found =
+                        state.iter().position(|ifa| is(ifa, index, address));
+
+                    if let Some(i) = found {
+                        let ifa = state.remove(i);
+                        pending.push_back(Event::AddressRemoved(ifa));
+                    }
+                }
+                windows::Notification::Interface(index) => {
+                    // An interface that went away counts as going down.
+                    let (was, running) = match windows::is_running(index) {
+                        Some(running) => {
+                            (self.running.insert(index, running), running)
+                        }
+                        None => (self.running.remove(&index), false),
+                    };
+
+                    if was.is_none() || was == Some(running) {
+                        continue;
+                    }
+
+                    let name = name_of(state, index)?;
+
+                    pending.push_back(match running {
+                        true => Event::LinkUp { index, name },
+                        false => Event::LinkDown { index, name },
+                    });
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn running() -> io::Result<HashMap<u32, bool>> {
+    let names = names()?.into_iter();
+    let running = |index| windows::is_running(index).map(|r| (index, r));
+    Ok(names.filter_map(|(index, _)| running(index)).collect())
+}
+
+#[cfg(target_os = "windows")]
+fn name_of(state: &[Interface], index: u32) -> io::Result<String> {
+    if let Some(ifa) = state.iter().find(|ifa| ifa.index == index) {
+        return Ok(ifa.name.clone());
+    }
+
+    let mut names = names()?.into_iter();
+    let name = names.find_map(|(i, name)| (i == index).then_some(name));
+    Ok(name.unwrap_or_default())
+}